@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
 #[command(name = "ccline")]
@@ -31,6 +31,31 @@ pub struct Cli {
     /// Patch Claude Code cli.js to disable context warnings
     #[arg(long = "patch")]
     pub patch: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Show token and cost totals across all projects
+    Stats {
+        /// Period for the per-project and per-model breakdowns: today, week or month
+        #[arg(short = 'p', long = "period", default_value = "month")]
+        period: String,
+
+        /// Number of days in the daily table
+        #[arg(short = 'd', long = "days", default_value_t = 7)]
+        days: u32,
+    },
+
+    /// Refresh the usage cache (started in the background by the usage segment)
+    #[command(hide = true)]
+    RefreshUsage,
+
+    /// Rescan transcripts for the spend summary (started in the background by the spend segment)
+    #[command(hide = true)]
+    RefreshSpend,
 }
 
 impl Cli {
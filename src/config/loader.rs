@@ -1,4 +1,6 @@
+use super::models::ModelConfig;
 use super::types::Config;
+use crate::utils::state_file::write_atomic;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -50,7 +52,7 @@ impl ConfigLoader {
             if !theme_path.exists() {
                 let theme_config = crate::ui::themes::ThemePresets::get_theme(theme_name);
                 let content = toml::to_string_pretty(&theme_config)?;
-                fs::write(&theme_path, content)?;
+                write_atomic(&theme_path, content.as_bytes())?;
                 println!("Created theme file: {}", theme_path.display());
                 created_any = true;
             }
@@ -103,7 +105,7 @@ impl ConfigLoader {
             if !theme_path.exists() {
                 let theme_config = crate::ui::themes::ThemePresets::get_theme(theme_name);
                 let content = toml::to_string_pretty(&theme_config)?;
-                fs::write(&theme_path, content)?;
+                write_atomic(&theme_path, content.as_bytes())?;
             }
         }
 
@@ -151,7 +153,7 @@ impl Config {
         if let Ok(entries) = fs::read_dir(&themes_dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
-                if path.extension().map_or(false, |ext| ext == "toml") {
+                if path.extension().is_some_and(|ext| ext == "toml") {
                     fs::copy(&path, &config_path)?;
                     return Ok(());
                 }
@@ -167,14 +169,10 @@ impl Config {
     /// Save configuration to default location
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let config_path = Self::get_config_path();
-
-        // Ensure config directory exists
-        if let Some(parent) = config_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
         let content = toml::to_string_pretty(self)?;
-        fs::write(config_path, content)?;
+        // Written to a temporary file and renamed, so a statusline rendering
+        // meanwhile never reads a half-written config
+        write_atomic(&config_path, content.as_bytes())?;
         Ok(())
     }
 
@@ -224,6 +222,17 @@ impl Config {
             }
         }
 
+        if let Some(ca_bundle) = self.network.as_ref().and_then(|n| n.ca_bundle.as_ref()) {
+            if !crate::utils::expand_home(ca_bundle).is_file() {
+                return Err(format!("CA bundle not found: {}", ca_bundle).into());
+            }
+        }
+
+        let invalid = ModelConfig::load().invalid_patterns();
+        if !invalid.is_empty() {
+            return Err(invalid.join("\n").into());
+        }
+
         Ok(())
     }
 
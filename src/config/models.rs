@@ -1,6 +1,9 @@
+use super::{ColorConfig, NormalizedUsage};
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelConfig {
@@ -8,13 +11,243 @@ pub struct ModelConfig {
     pub model_entries: Vec<ModelEntry>,
 }
 
+/// How an entry's `pattern` and `aliases` are compared with the model ID
+///
+/// All modes ignore case.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchMode {
+    /// Pattern occurs anywhere in the ID
+    #[default]
+    Contains,
+    Exact,
+    Prefix,
+    /// `*` matches any run of characters, `?` a single one
+    Glob,
+    Regex,
+}
+
+impl MatchMode {
+    fn is_default(&self) -> bool {
+        *self == MatchMode::Contains
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelEntry {
     pub pattern: String,
+    #[serde(
+        rename = "match",
+        default,
+        skip_serializing_if = "MatchMode::is_default"
+    )]
+    pub match_mode: MatchMode,
+    /// Entries with a higher priority win; ties go to the entry listed first
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub priority: i32,
+    /// Further patterns matched with the same mode
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
     pub display_name: String,
     pub context_limit: u32,
+    /// Fraction of the context window at which Claude Code auto-compacts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_compact_threshold: Option<f64>,
+    /// Output space the model reserves out of the context window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+    /// Replaces the model segment icon for this model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Overrides the model segment colors that are set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub colors: Option<ColorConfig>,
+    /// Prices used to compute session cost from transcript usage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<ModelPricing>,
+    /// Compiled `glob` / `regex` patterns, built once per entry
+    #[serde(skip)]
+    regexes: OnceLock<Vec<Regex>>,
+}
+
+/// Token prices in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    /// Defaults to 1.25× the input price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write: Option<f64>,
+    /// Defaults to 0.1× the input price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+}
+
+impl ModelPricing {
+    const fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write: None,
+            cache_read: None,
+        }
+    }
+
+    /// Cost in USD of one response's usage
+    pub fn cost(&self, usage: &NormalizedUsage) -> f64 {
+        self.cost_for(
+            usage.input_tokens as u64,
+            usage.output_tokens as u64,
+            usage.cache_creation_input_tokens as u64,
+            usage.cache_read_input_tokens as u64,
+        )
+    }
+
+    /// Cost in USD of the given token counts
+    pub fn cost_for(&self, input: u64, output: u64, cache_write: u64, cache_read: u64) -> f64 {
+        (input as f64 * self.input
+            + output as f64 * self.output
+            + cache_write as f64 * self.cache_write_price()
+            + cache_read as f64 * self.cache_read_price())
+            / 1_000_000.0
+    }
+
+    /// USD saved by prompt caching versus sending every cached token as plain
+    /// input; negative when cache writes cost more than reads saved
+    pub fn cache_savings(&self, usage: &NormalizedUsage) -> f64 {
+        self.cache_savings_for(
+            usage.cache_creation_input_tokens as u64,
+            usage.cache_read_input_tokens as u64,
+        )
+    }
+
+    /// Cache savings in USD of the given token counts
+    pub fn cache_savings_for(&self, cache_write: u64, cache_read: u64) -> f64 {
+        let read_discount = self.input - self.cache_read_price();
+        let write_premium = self.cache_write_price() - self.input;
+        (cache_read as f64 * read_discount - cache_write as f64 * write_premium) / 1_000_000.0
+    }
+
+    fn cache_write_price(&self) -> f64 {
+        self.cache_write.unwrap_or(self.input * 1.25)
+    }
+
+    fn cache_read_price(&self) -> f64 {
+        self.cache_read.unwrap_or(self.input * 0.1)
+    }
+}
+
+/// Anthropic list prices, used when no models.toml entry sets `pricing`;
+/// first substring match wins
+const BUILTIN_PRICING: &[(&str, ModelPricing)] = &[
+    ("opus-4-5", ModelPricing::new(5.0, 25.0)),
+    ("opus", ModelPricing::new(15.0, 75.0)),
+    ("sonnet", ModelPricing::new(3.0, 15.0)),
+    ("haiku-4-5", ModelPricing::new(1.0, 5.0)),
+    ("3-5-haiku", ModelPricing::new(0.8, 4.0)),
+    ("haiku", ModelPricing::new(0.25, 1.25)),
+];
+
+/// Built-in entries rank below every user entry at the default priority
+const BUILTIN_PRIORITY: i32 = -10;
+
+fn is_zero(value: &i32) -> bool {
+    *value == 0
+}
+
+impl ModelEntry {
+    /// Entry matched with the default `contains` mode and no extras
+    fn simple(pattern: &str, display_name: &str, context_limit: u32) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            match_mode: MatchMode::Contains,
+            priority: BUILTIN_PRIORITY,
+            aliases: Vec::new(),
+            display_name: display_name.to_string(),
+            context_limit,
+            auto_compact_threshold: None,
+            max_output_tokens: None,
+            icon: None,
+            colors: None,
+            pricing: None,
+            regexes: OnceLock::new(),
+        }
+    }
+
+    /// `None` for match modes that do not compile a pattern
+    fn compile(&self, pattern: &str) -> Option<Result<Regex, regex::Error>> {
+        let source = match self.match_mode {
+            MatchMode::Regex => pattern.to_string(),
+            MatchMode::Glob => {
+                let mut source = String::from("^");
+                for ch in pattern.chars() {
+                    match ch {
+                        '*' => source.push_str(".*"),
+                        '?' => source.push('.'),
+                        _ => source.push_str(&regex::escape(&ch.to_string())),
+                    }
+                }
+                source.push('$');
+                source
+            }
+            _ => return None,
+        };
+        Some(RegexBuilder::new(&source).case_insensitive(true).build())
+    }
+
+    /// Compiled `glob` / `regex` patterns; invalid ones never match and are
+    /// left for `ccline --check` to report
+    fn regexes(&self) -> &[Regex] {
+        self.regexes.get_or_init(|| {
+            std::iter::once(&self.pattern)
+                .chain(&self.aliases)
+                .filter_map(|pattern| self.compile(pattern)?.ok())
+                .collect()
+        })
+    }
+
+    fn matches_pattern(&self, pattern: &str, model_lower: &str) -> bool {
+        let pattern_lower = pattern.to_lowercase();
+        match self.match_mode {
+            MatchMode::Contains => model_lower.contains(&pattern_lower),
+            MatchMode::Exact => model_lower == pattern_lower,
+            MatchMode::Prefix => model_lower.starts_with(&pattern_lower),
+            // Compared through the compiled `regexes` instead
+            MatchMode::Glob | MatchMode::Regex => false,
+        }
+    }
+
+    /// Override the segment colors this entry sets
+    pub fn apply_colors(&self, colors: &mut ColorConfig) {
+        if let Some(overrides) = &self.colors {
+            if overrides.icon.is_some() {
+                colors.icon = overrides.icon.clone();
+            }
+            if overrides.text.is_some() {
+                colors.text = overrides.text.clone();
+            }
+            if overrides.background.is_some() {
+                colors.background = overrides.background.clone();
+            }
+        }
+    }
+
+    /// Whether the pattern or any alias matches the model ID
+    pub fn matches(&self, model_id: &str) -> bool {
+        if matches!(self.match_mode, MatchMode::Glob | MatchMode::Regex) {
+            return self.regexes().iter().any(|regex| regex.is_match(model_id));
+        }
+        let model_lower = model_id.to_lowercase();
+        std::iter::once(&self.pattern)
+            .chain(&self.aliases)
+            .any(|pattern| self.matches_pattern(pattern, &model_lower))
+    }
 }
 
+/// Approximate point where Claude Code triggers auto-compact when a model
+/// entry does not say otherwise
+pub const DEFAULT_AUTO_COMPACT_THRESHOLD: f64 = 0.8;
+
 impl ModelConfig {
     /// Load model configuration from TOML file
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
@@ -24,7 +257,33 @@ impl ModelConfig {
     }
 
     /// Load model configuration with fallback locations
-    pub fn load() -> Self {
+    ///
+    /// Read and compiled once per process; later calls share it.
+    pub fn load() -> &'static Self {
+        static LOADED: OnceLock<ModelConfig> = OnceLock::new();
+        LOADED.get_or_init(|| {
+            let config = Self::read();
+            for entry in &config.model_entries {
+                entry.regexes();
+            }
+            config
+        })
+    }
+
+    /// Patterns and aliases that fail to compile, one message each
+    pub fn invalid_patterns(&self) -> Vec<String> {
+        let mut invalid = Vec::new();
+        for entry in &self.model_entries {
+            for pattern in std::iter::once(&entry.pattern).chain(&entry.aliases) {
+                if let Some(Err(e)) = entry.compile(pattern) {
+                    invalid.push(format!("Invalid model pattern '{}': {}", pattern, e));
+                }
+            }
+        }
+        invalid
+    }
+
+    fn read() -> Self {
         let mut model_config = Self::default();
 
         // First, try to create default models.toml if it doesn't exist
@@ -57,6 +316,18 @@ impl ModelConfig {
         model_config
     }
 
+    /// Find the entry for a model: highest priority first, then file order
+    /// (external config before built-in)
+    pub fn find(&self, model_id: &str) -> Option<&ModelEntry> {
+        self.model_entries
+            .iter()
+            .filter(|entry| entry.matches(model_id))
+            .fold(None, |best: Option<&ModelEntry>, entry| match best {
+                Some(best) if best.priority >= entry.priority => Some(best),
+                _ => Some(entry),
+            })
+    }
+
     /// Get context limit for a model based on ID pattern matching
     /// Checks external config first, then falls back to built-in config
     pub fn get_context_limit(&self, model_id: &str) -> u32 {
@@ -66,31 +337,48 @@ impl ModelConfig {
     /// Try to get context limit for a model, returns None if no match found
     /// Use this to check if there's a matching config entry for the model
     pub fn try_get_context_limit(&self, model_id: &str) -> Option<u32> {
-        let model_lower = model_id.to_lowercase();
-
-        for entry in &self.model_entries {
-            if model_lower.contains(&entry.pattern.to_lowercase()) {
-                return Some(entry.context_limit);
-            }
-        }
-
-        None
+        self.find(model_id).map(|entry| entry.context_limit)
     }
 
     /// Get display name for a model based on ID pattern matching
     /// Checks external config first, then falls back to built-in config
     /// Returns None if no match found (should use fallback display_name)
     pub fn get_display_name(&self, model_id: &str) -> Option<String> {
-        let model_lower = model_id.to_lowercase();
+        self.find(model_id).map(|entry| entry.display_name.clone())
+    }
 
-        // Check model entries
-        for entry in &self.model_entries {
-            if model_lower.contains(&entry.pattern.to_lowercase()) {
-                return Some(entry.display_name.clone());
-            }
-        }
+    /// Get the auto-compact threshold (fraction of the context window)
+    pub fn get_auto_compact_threshold(&self, model_id: &str) -> f64 {
+        self.find(model_id)
+            .and_then(|entry| entry.auto_compact_threshold)
+            .filter(|t| *t > 0.0 && *t <= 1.0)
+            .unwrap_or(DEFAULT_AUTO_COMPACT_THRESHOLD)
+    }
+
+    pub fn get_max_output_tokens(&self, model_id: &str) -> Option<u32> {
+        self.find(model_id)
+            .and_then(|entry| entry.max_output_tokens)
+    }
+
+    /// Prices for a model: the matching entry's `pricing`, else built-in Claude prices
+    pub fn get_pricing(&self, model_id: &str) -> Option<ModelPricing> {
+        self.find(model_id)
+            .and_then(|entry| entry.pricing)
+            .or_else(|| {
+                let model_lower = model_id.to_lowercase();
+                BUILTIN_PRICING
+                    .iter()
+                    .find(|(pattern, _)| model_lower.contains(pattern))
+                    .map(|(_, pricing)| *pricing)
+            })
+    }
 
-        None
+    /// Context size at which auto-compact triggers, after reserving output space
+    pub fn get_compact_threshold_tokens(&self, model_id: &str) -> u32 {
+        let usable = self
+            .get_context_limit(model_id)
+            .saturating_sub(self.get_max_output_tokens(model_id).unwrap_or(0));
+        (usable as f64 * self.get_auto_compact_threshold(model_id)) as u32
     }
 
     /// Create default model configuration file with minimal template
@@ -112,13 +400,21 @@ impl ModelConfig {
              \n\
              # Model configurations\n\
              # Each [[models]] section defines a model pattern and its properties\n\
-             # Order matters: first match wins, so put more specific patterns first\n\
+             # The entry with the highest priority wins; among equal priorities the first match wins\n\
              \n\
              # Example of how to add new models:\n\
              # [[models]]\n\
              # pattern = \"glm-4.5\"\n\
+             # match = \"prefix\"             # contains (default), exact, prefix, glob or regex\n\
+             # aliases = [\"zai-glm-4.5\"]     # optional, matched with the same mode\n\
+             # priority = 10                  # optional, default 0\n\
              # display_name = \"GLM-4.5\"\n\
-             # context_limit = 128000\n",
+             # context_limit = 128000\n\
+             # auto_compact_threshold = 0.8  # optional, fraction of context_limit\n\
+             # max_output_tokens = 16000     # optional, reserved out of context_limit\n\
+             # icon = \"🧠\"                   # optional, replaces the model segment icon\n\
+             # colors = {{ text = {{ c16 = 10 }} }}  # optional icon/text/background overrides\n\
+             # pricing = {{ input = 0.6, output = 2.2, cache_read = 0.11 }}  # optional, USD per million tokens\n",
             toml_content.trim()
         );
 
@@ -136,11 +432,13 @@ impl Default for ModelConfig {
     fn default() -> Self {
         Self {
             model_entries: vec![
-                // 1M context models (put first for priority matching)
+                // 1M context variants carry a "[1m]" suffix on the model ID
                 ModelEntry {
-                    pattern: "[1m]".to_string(),
-                    display_name: "Sonnet 4.5 1M".to_string(),
-                    context_limit: 1_000_000,
+                    pattern: r"\[1m\]$".to_string(),
+                    match_mode: MatchMode::Regex,
+                    // Ahead of the other built-ins, still behind user entries
+                    priority: BUILTIN_PRIORITY + 1,
+                    ..ModelEntry::simple("", "Sonnet 4.5 1M", 1_000_000)
                 },
                 // ModelEntry {
                 //     pattern: "claude-sonnet-4-5".to_string(),
@@ -167,33 +465,162 @@ impl Default for ModelConfig {
                 //     display_name: "Sonnet 4".to_string(),
                 //     context_limit: 200_000,
                 // },
-                ModelEntry {
-                    pattern: "claude-3-7-sonnet".to_string(),
-                    display_name: "Sonnet 3.7".to_string(),
-                    context_limit: 200_000,
-                },
+                ModelEntry::simple("claude-3-7-sonnet", "Sonnet 3.7", 200_000),
                 // Third-party models
-                ModelEntry {
-                    pattern: "glm-4.5".to_string(),
-                    display_name: "GLM-4.5".to_string(),
-                    context_limit: 128_000,
-                },
-                ModelEntry {
-                    pattern: "kimi-k2-turbo".to_string(),
-                    display_name: "Kimi K2 Turbo".to_string(),
-                    context_limit: 128_000,
-                },
-                ModelEntry {
-                    pattern: "kimi-k2".to_string(),
-                    display_name: "Kimi K2".to_string(),
-                    context_limit: 128_000,
-                },
-                ModelEntry {
-                    pattern: "qwen3-coder".to_string(),
-                    display_name: "Qwen Coder".to_string(),
-                    context_limit: 256_000,
-                },
+                ModelEntry::simple("glm-4.5", "GLM-4.5", 128_000),
+                ModelEntry::simple("kimi-k2-turbo", "Kimi K2 Turbo", 128_000),
+                ModelEntry::simple("kimi-k2", "Kimi K2", 128_000),
+                ModelEntry::simple("qwen3-coder", "Qwen Coder", 256_000),
             ],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// User entries ahead of the built-in ones, as `load` merges them
+    fn config(user_toml: &str) -> ModelConfig {
+        let mut config: ModelConfig = toml::from_str(user_toml).unwrap();
+        config
+            .model_entries
+            .extend(ModelConfig::default().model_entries);
+        config
+    }
+
+    fn display_name(config: &ModelConfig, model_id: &str) -> Option<String> {
+        config.get_display_name(model_id)
+    }
+
+    #[test]
+    fn match_modes_ignore_case() {
+        let config = config(
+            r#"
+            [[models]]
+            pattern = "GLM"
+            display_name = "contains"
+            context_limit = 1
+
+            [[models]]
+            pattern = "deepseek-v3"
+            match = "exact"
+            display_name = "exact"
+            context_limit = 1
+
+            [[models]]
+            pattern = "Qwen"
+            match = "prefix"
+            display_name = "prefix"
+            context_limit = 1
+
+            [[models]]
+            pattern = "gpt-?o*"
+            match = "glob"
+            display_name = "glob"
+            context_limit = 1
+
+            [[models]]
+            pattern = "^mistral-(large|medium)$"
+            match = "regex"
+            display_name = "regex"
+            context_limit = 1
+            "#,
+        );
+
+        assert_eq!(
+            display_name(&config, "zai-glm-4.6").as_deref(),
+            Some("contains")
+        );
+        assert_eq!(
+            display_name(&config, "DeepSeek-V3").as_deref(),
+            Some("exact")
+        );
+        assert_eq!(display_name(&config, "deepseek-v3.1"), None);
+        assert_eq!(
+            display_name(&config, "qwen3-max").as_deref(),
+            Some("prefix")
+        );
+        assert_eq!(display_name(&config, "my-qwen"), None);
+        assert_eq!(
+            display_name(&config, "GPT-4o-mini").as_deref(),
+            Some("glob")
+        );
+        assert_eq!(display_name(&config, "gpt-4.1"), None);
+        assert_eq!(
+            display_name(&config, "Mistral-Large").as_deref(),
+            Some("regex")
+        );
+        assert_eq!(display_name(&config, "mistral-small"), None);
+    }
+
+    #[test]
+    fn highest_priority_wins_and_ties_go_to_the_first_entry() {
+        let config = config(
+            r#"
+            [[models]]
+            pattern = "opus"
+            display_name = "first"
+            context_limit = 1
+
+            [[models]]
+            pattern = "claude-opus"
+            match = "prefix"
+            display_name = "second"
+            context_limit = 1
+
+            [[models]]
+            pattern = "claude-opus-4-*"
+            match = "glob"
+            priority = 5
+            display_name = "prioritized"
+            context_limit = 1
+            "#,
+        );
+
+        assert_eq!(
+            display_name(&config, "claude-opus-3").as_deref(),
+            Some("first")
+        );
+        assert_eq!(
+            display_name(&config, "claude-opus-4-1").as_deref(),
+            Some("prioritized")
+        );
+    }
+
+    #[test]
+    fn user_entries_beat_builtin_ones() {
+        let builtin = config("models = []");
+        assert_eq!(
+            builtin.get_context_limit("claude-sonnet-4-5[1m]"),
+            1_000_000
+        );
+
+        let config = config(
+            r#"
+            [[models]]
+            pattern = "claude-sonnet-4-5"
+            display_name = "Sonnet"
+            context_limit = 200000
+            "#,
+        );
+        assert_eq!(config.get_context_limit("claude-sonnet-4-5[1m]"), 200_000);
+    }
+
+    #[test]
+    fn invalid_patterns_never_match() {
+        let config = config(
+            r#"
+            [[models]]
+            pattern = "claude-(opus"
+            match = "regex"
+            aliases = ["^opus$"]
+            display_name = "Opus"
+            context_limit = 1
+            "#,
+        );
+
+        assert_eq!(display_name(&config, "claude-(opus"), None);
+        assert_eq!(display_name(&config, "opus").as_deref(), Some("Opus"));
+    }
+}
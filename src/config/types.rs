@@ -9,6 +9,114 @@ pub struct Config {
     /// Deprecated: theme field is ignored. Kept for backwards compatibility with old config files.
     #[serde(default, skip_serializing)]
     pub theme: String,
+    /// Spending caps checked by cost-related segments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetConfig>,
+    /// Where the OAuth token for API-backed segments comes from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<CredentialsConfig>,
+    /// Offline switch, proxy, CA bundle and timeout for every HTTP request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<NetworkConfig>,
+}
+
+/// A source of the Claude OAuth token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialProvider {
+    /// `CLAUDE_CODE_OAUTH_TOKEN` environment variable
+    Env,
+    /// `credentials.file`
+    File,
+    /// `.credentials.json` in `CLAUDE_CONFIG_DIR`
+    ConfigDir,
+    /// macOS keychain, then `~/.claude/.credentials.json`
+    Default,
+    /// stdout of `credentials.credential_command`
+    Command,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialsConfig {
+    /// Providers tried in order
+    #[serde(default = "default_credential_providers")]
+    pub providers: Vec<CredentialProvider>,
+    /// Credentials JSON file for the `file` provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Shell command printing a token or credentials JSON, for the `command` provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential_command: Option<String>,
+}
+
+impl Default for CredentialsConfig {
+    fn default() -> Self {
+        Self {
+            providers: default_credential_providers(),
+            file: None,
+            credential_command: None,
+        }
+    }
+}
+
+fn default_credential_providers() -> Vec<CredentialProvider> {
+    vec![
+        CredentialProvider::Env,
+        CredentialProvider::File,
+        CredentialProvider::ConfigDir,
+        CredentialProvider::Default,
+        CredentialProvider::Command,
+    ]
+}
+
+/// Network policy shared by the usage segment and the update check
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Make no requests at all; segments render from their caches
+    #[serde(default)]
+    pub offline: bool,
+    /// Proxy URL, e.g. "http://proxy.corp:3128"; defaults to `HTTPS_PROXY`/`HTTP_PROXY`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Hosts reached directly, e.g. ["localhost", ".corp.example"]; defaults to `NO_PROXY`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+    /// PEM file of extra root certificates, for TLS-intercepting proxies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<String>,
+    /// Request timeout in seconds, unless a segment sets its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+/// Spending caps in USD; each one is optional
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekly: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session: Option<f64>,
+    /// Fraction of a cap at which segments switch to `warn_color`
+    #[serde(default = "default_budget_warn_at")]
+    pub warn_at: f64,
+    #[serde(default = "default_budget_warn_color")]
+    pub warn_color: AnsiColor,
+    #[serde(default = "default_budget_over_color")]
+    pub over_color: AnsiColor,
+}
+
+fn default_budget_warn_at() -> f64 {
+    0.8
+}
+
+fn default_budget_warn_color() -> AnsiColor {
+    AnsiColor::Color16 { c16: 11 } // Bright yellow
+}
+
+fn default_budget_over_color() -> AnsiColor {
+    AnsiColor::Color16 { c16: 9 } // Bright red
 }
 
 // Default implementation moved to ui/themes/presets.rs
@@ -75,6 +183,10 @@ pub enum SegmentId {
     Session,
     OutputStyle,
     Update,
+    BurnRate,
+    Spend,
+    CacheEfficiency,
+    Plan,
 }
 
 // Legacy compatibility structure
@@ -96,6 +208,9 @@ pub struct Model {
 #[derive(Deserialize)]
 pub struct Workspace {
     pub current_dir: String,
+    /// Directory Claude Code was started in
+    #[serde(default)]
+    pub project_dir: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -119,6 +234,9 @@ pub struct InputData {
     pub transcript_path: String,
     pub cost: Option<Cost>,
     pub output_style: Option<OutputStyle>,
+    /// Claude Code version, e.g. "2.0.14"
+    #[serde(default)]
+    pub version: Option<String>,
 }
 
 // OpenAI-style nested token details
@@ -291,6 +409,20 @@ impl Config {
     }
 }
 
+impl AnsiColor {
+    /// SGR escape sequence selecting this color as the foreground
+    pub fn fg_sequence(&self) -> String {
+        match self {
+            AnsiColor::Color16 { c16 } => {
+                let code = if *c16 < 8 { 30 + c16 } else { 90 + (c16 - 8) };
+                format!("\x1b[{}m", code)
+            }
+            AnsiColor::Color256 { c256 } => format!("\x1b[38;5;{}m", c256),
+            AnsiColor::Rgb { r, g, b } => format!("\x1b[38;2;{};{};{}m", r, g, b),
+        }
+    }
+}
+
 impl PartialEq for AnsiColor {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -394,20 +526,3 @@ impl RawUsage {
 
 // Legacy alias for backward compatibility
 pub type Usage = RawUsage;
-
-#[derive(Deserialize)]
-pub struct Message {
-    pub usage: Option<Usage>,
-}
-
-#[derive(Deserialize)]
-pub struct TranscriptEntry {
-    pub r#type: Option<String>,
-    pub message: Option<Message>,
-    #[serde(rename = "leafUuid")]
-    pub leaf_uuid: Option<String>,
-    pub uuid: Option<String>,
-    #[serde(rename = "parentUuid")]
-    pub parent_uuid: Option<String>,
-    pub summary: Option<String>,
-}
@@ -0,0 +1,335 @@
+//! Progress bar rendering for percentage metadata
+//!
+//! Any segment exposing a 0–100 value in its metadata can show a bar by
+//! setting `bar = true` in its options. The bar is prepended to the
+//! segment's primary text.
+
+use crate::config::{AnsiColor, SegmentConfig, SegmentId};
+use crate::core::segments::SegmentData;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Partial cells from 1/8 to 7/8 width
+const EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Color applied from a percentage upwards
+#[derive(Debug, Clone, Deserialize)]
+pub struct BarThreshold {
+    pub at: f64,
+    pub color: AnsiColor,
+}
+
+#[derive(Debug, Clone)]
+pub enum BarStyle {
+    /// Full blocks with eighth-width precision for the last cell
+    Blocks,
+    /// Whole-cell fill glyph, e.g. `●`
+    Glyphs { fill: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct ProgressBar {
+    pub width: usize,
+    pub style: BarStyle,
+    /// Glyph for unfilled cells
+    pub empty: String,
+    pub fill_color: Option<AnsiColor>,
+    pub empty_color: Option<AnsiColor>,
+    /// Sorted ascending by `at`
+    pub thresholds: Vec<BarThreshold>,
+    /// Color each cell by its own position instead of the whole bar by the value
+    pub gradient: bool,
+}
+
+impl Default for ProgressBar {
+    fn default() -> Self {
+        Self {
+            width: 10,
+            style: BarStyle::Blocks,
+            empty: " ".to_string(),
+            fill_color: None,
+            empty_color: None,
+            thresholds: Vec::new(),
+            gradient: false,
+        }
+    }
+}
+
+impl ProgressBar {
+    /// Build a bar from segment options; `None` unless `bar = true`
+    pub fn from_options(options: &HashMap<String, Value>) -> Option<Self> {
+        if !options
+            .get("bar")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+        {
+            return None;
+        }
+
+        let mut bar = Self::default();
+
+        if let Some(width) = options.get("bar_width").and_then(|v| v.as_u64()) {
+            bar.width = width.clamp(1, 100) as usize;
+        }
+
+        if let Some(fill) = options.get("bar_fill").and_then(|v| v.as_str()) {
+            bar.style = BarStyle::Glyphs {
+                fill: fill.to_string(),
+            };
+            bar.empty = "░".to_string();
+        }
+        if let Some(empty) = options.get("bar_empty").and_then(|v| v.as_str()) {
+            bar.empty = empty.to_string();
+        }
+
+        bar.fill_color = options
+            .get("bar_color")
+            .and_then(|v| serde_json::from_value(v.clone()).ok());
+        bar.empty_color = options
+            .get("bar_empty_color")
+            .and_then(|v| serde_json::from_value(v.clone()).ok());
+        bar.thresholds = options
+            .get("bar_thresholds")
+            .and_then(|v| serde_json::from_value::<Vec<BarThreshold>>(v.clone()).ok())
+            .unwrap_or_default();
+        bar.thresholds
+            .sort_by(|a, b| a.at.partial_cmp(&b.at).unwrap_or(std::cmp::Ordering::Equal));
+        bar.gradient = options
+            .get("bar_gradient")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        Some(bar)
+    }
+
+    fn color_at(&self, percent: f64) -> Option<&AnsiColor> {
+        self.thresholds
+            .iter()
+            .rev()
+            .find(|t| percent >= t.at)
+            .map(|t| &t.color)
+            .or(self.fill_color.as_ref())
+    }
+
+    /// Render the bar for a value between 0 and 100
+    pub fn render(&self, percent: f64) -> String {
+        let percent = if percent.is_finite() {
+            percent.clamp(0.0, 100.0)
+        } else {
+            0.0
+        };
+
+        // Total filled width measured in eighths of a cell
+        let eighths = (percent / 100.0 * (self.width * 8) as f64).round() as usize;
+        let full_cells = eighths / 8;
+        let remainder = eighths % 8;
+
+        let mut cells: Vec<(String, bool)> = Vec::with_capacity(self.width);
+        match &self.style {
+            BarStyle::Blocks => {
+                for _ in 0..full_cells {
+                    cells.push(("█".to_string(), true));
+                }
+                if remainder > 0 && cells.len() < self.width {
+                    cells.push((EIGHTHS[remainder - 1].to_string(), true));
+                }
+                while cells.len() < self.width {
+                    cells.push((self.empty.clone(), false));
+                }
+            }
+            BarStyle::Glyphs { fill } => {
+                // Whole glyphs only: round to the nearest cell
+                let filled = (eighths + 4) / 8;
+                for i in 0..self.width {
+                    if i < filled {
+                        cells.push((fill.clone(), true));
+                    } else {
+                        cells.push((self.empty.clone(), false));
+                    }
+                }
+            }
+        }
+
+        let value_color = self.color_at(percent);
+        let mut out = String::new();
+        let mut active: Option<String> = None;
+
+        for (i, (glyph, filled)) in cells.iter().enumerate() {
+            let color = if *filled {
+                if self.gradient {
+                    let cell_start = i as f64 / self.width as f64 * 100.0;
+                    self.color_at(cell_start)
+                } else {
+                    value_color
+                }
+            } else {
+                self.empty_color.as_ref()
+            };
+
+            let code = color.map(|c| c.fg_sequence());
+            if code != active {
+                out.push_str(code.as_deref().unwrap_or("\x1b[39m"));
+                active = code;
+            }
+            out.push_str(glyph);
+        }
+
+        if active.is_some() {
+            out.push_str("\x1b[39m");
+        }
+        out
+    }
+}
+
+/// Metadata key holding the percentage a segment's bar shows by default
+fn default_metric(id: SegmentId) -> Option<&'static str> {
+    match id {
+        SegmentId::ContextWindow => Some("percentage"),
+        SegmentId::Usage => Some("five_hour_utilization"),
+        _ => None,
+    }
+}
+
+/// Prepend a progress bar to the segment's primary text when configured
+///
+/// `bar_metric` selects the metadata key to visualise; segments with an
+/// obvious percentage pick one by default.
+pub fn apply_bar(config: &SegmentConfig, data: &mut SegmentData) {
+    let Some(bar) = ProgressBar::from_options(&config.options) else {
+        return;
+    };
+
+    let metric = config
+        .options
+        .get("bar_metric")
+        .and_then(|v| v.as_str())
+        .or_else(|| default_metric(config.id));
+    let Some(percent) = metric
+        .and_then(|key| data.metadata.get(key))
+        .and_then(|value| value.parse::<f64>().ok())
+    else {
+        return;
+    };
+
+    // The bar resets the foreground, so restore the segment text color after it
+    let restore = config
+        .colors
+        .text
+        .as_ref()
+        .map(|c| c.fg_sequence())
+        .unwrap_or_default();
+    data.primary = format!("{}{} {}", bar.render(percent), restore, data.primary);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const RED: AnsiColor = AnsiColor::Color16 { c16: 1 };
+    const GREEN: AnsiColor = AnsiColor::Color16 { c16: 2 };
+    const YELLOW: AnsiColor = AnsiColor::Color16 { c16: 3 };
+
+    fn bar(width: usize) -> ProgressBar {
+        ProgressBar {
+            width,
+            ..Default::default()
+        }
+    }
+
+    fn options(value: serde_json::Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn last_cell_rounds_to_the_nearest_eighth() {
+        assert_eq!(bar(4).render(0.0), "    ");
+        assert_eq!(bar(4).render(50.0), "██  ");
+        // 4 cells are 32 eighths: 30% is 9.6, rounded to 10 (one cell and 2/8)
+        assert_eq!(bar(4).render(30.0), "█▎  ");
+        // 1% of 32 eighths rounds down to nothing
+        assert_eq!(bar(4).render(1.0), "    ");
+        assert_eq!(bar(4).render(100.0), "████");
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped() {
+        assert_eq!(bar(3).render(-20.0), "   ");
+        assert_eq!(bar(3).render(250.0), "███");
+        assert_eq!(bar(3).render(f64::NAN), "   ");
+        assert_eq!(bar(3).render(f64::INFINITY), "   ");
+    }
+
+    #[test]
+    fn glyph_bars_fill_whole_cells() {
+        let bar = ProgressBar::from_options(&options(json!({
+            "bar": true,
+            "bar_width": 5,
+            "bar_fill": "●",
+        })))
+        .unwrap();
+        assert_eq!(bar.render(0.0), "░░░░░");
+        assert_eq!(bar.render(45.0), "●●░░░");
+        assert_eq!(bar.render(55.0), "●●●░░");
+        assert_eq!(bar.render(100.0), "●●●●●");
+    }
+
+    #[test]
+    fn bar_is_off_unless_enabled() {
+        assert!(ProgressBar::from_options(&options(json!({ "bar_width": 5 }))).is_none());
+        assert!(ProgressBar::from_options(&options(json!({ "bar": false }))).is_none());
+    }
+
+    #[test]
+    fn highest_reached_threshold_colors_the_bar() {
+        let bar = ProgressBar::from_options(&options(json!({
+            "bar": true,
+            "bar_width": 2,
+            "bar_color": { "c16": 2 },
+            // Listed out of order on purpose
+            "bar_thresholds": [
+                { "at": 90, "color": { "c16": 1 } },
+                { "at": 60, "color": { "c16": 3 } },
+            ],
+        })))
+        .unwrap();
+
+        let green = GREEN.fg_sequence();
+        let yellow = YELLOW.fg_sequence();
+        let red = RED.fg_sequence();
+        assert_eq!(bar.render(50.0), format!("{green}█\x1b[39m "));
+        assert_eq!(bar.render(60.0), format!("{yellow}█▎\x1b[39m"));
+        assert_eq!(bar.render(95.0), format!("{red}█▉\x1b[39m"));
+    }
+
+    #[test]
+    fn gradient_colors_each_cell_by_its_position() {
+        let bar = ProgressBar {
+            width: 4,
+            gradient: true,
+            fill_color: Some(GREEN),
+            thresholds: vec![
+                BarThreshold {
+                    at: 50.0,
+                    color: YELLOW,
+                },
+                BarThreshold {
+                    at: 75.0,
+                    color: RED,
+                },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            bar.render(100.0),
+            format!(
+                "{}██{}█{}█\x1b[39m",
+                GREEN.fg_sequence(),
+                YELLOW.fg_sequence(),
+                RED.fg_sequence()
+            )
+        );
+    }
+}
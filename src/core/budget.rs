@@ -0,0 +1,288 @@
+//! Spending budgets evaluated against a local ledger
+//!
+//! Each render records the current session's cost. Increases since the last
+//! render are attributed to the day they were seen, so daily and weekly spend
+//! stay correct for sessions that run past midnight.
+
+use crate::config::{BudgetConfig, InputData, SegmentConfig, SegmentId};
+use crate::core::segments::{CostSegment, SegmentData};
+use crate::core::spend::SpendPeriod;
+use crate::utils::state_file::StateFile;
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Sessions idle for longer than this are dropped from the ledger
+const LEDGER_RETENTION_DAYS: i64 = 35;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetKind {
+    Daily,
+    Weekly,
+    Session,
+}
+
+impl BudgetKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Session => "session",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BudgetLevel {
+    Ok,
+    Warn,
+    Over,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BudgetStatus {
+    pub kind: BudgetKind,
+    pub spent: f64,
+    pub cap: f64,
+}
+
+impl BudgetStatus {
+    pub fn ratio(&self) -> f64 {
+        if self.cap > 0.0 {
+            self.spent / self.cap
+        } else {
+            0.0
+        }
+    }
+
+    pub fn level(&self, config: &BudgetConfig) -> BudgetLevel {
+        let ratio = self.ratio();
+        if ratio >= 1.0 {
+            BudgetLevel::Over
+        } else if ratio >= config.warn_at {
+            BudgetLevel::Warn
+        } else {
+            BudgetLevel::Ok
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionRecord {
+    /// Last cost seen for the session
+    cost: f64,
+    /// Cost increases attributed to each local day
+    days: BTreeMap<NaiveDate, f64>,
+    last_seen: NaiveDate,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BudgetLedger {
+    sessions: BTreeMap<String, SessionRecord>,
+}
+
+impl BudgetLedger {
+    fn file() -> Option<StateFile> {
+        StateFile::in_state_dir(".budget_ledger.json")
+    }
+
+    /// Record the session's current cost; returns whether anything changed
+    fn record(&mut self, session: &str, cost: f64, today: NaiveDate) -> bool {
+        let record = self.sessions.entry(session.to_string()).or_default();
+        // A lower cost means Claude Code restarted its counter (e.g. a resumed session)
+        let delta = if cost >= record.cost {
+            cost - record.cost
+        } else {
+            cost
+        };
+        let changed = delta > 0.0 || record.last_seen != today;
+
+        if delta > 0.0 {
+            *record.days.entry(today).or_default() += delta;
+        }
+        record.cost = cost;
+        record.last_seen = today;
+
+        let cutoff = today - chrono::Duration::days(LEDGER_RETENTION_DAYS);
+        let before = self.sessions.len();
+        self.sessions.retain(|_, record| record.last_seen >= cutoff);
+
+        changed || self.sessions.len() != before
+    }
+
+    fn spent_between(&self, from: NaiveDate, to: NaiveDate) -> f64 {
+        self.sessions
+            .values()
+            .flat_map(|record| record.days.range(from..=to).map(|(_, cost)| cost))
+            .sum()
+    }
+}
+
+/// Ledger key for the session a statusline render belongs to
+fn session_key(transcript_path: &str) -> String {
+    Path::new(transcript_path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| transcript_path.to_string())
+}
+
+/// Record the current session's cost and check it against every configured cap
+pub fn evaluate(config: &BudgetConfig, input: &InputData) -> Vec<BudgetStatus> {
+    let today = Local::now().date_naive();
+    let session_cost = CostSegment::session_cost(input);
+
+    // Recorded under the ledger lock, so sessions rendering at the same moment
+    // don't drop each other's entries
+    let file = BudgetLedger::file();
+    let ledger: BudgetLedger = match session_cost {
+        Some(cost) => file.and_then(|file| {
+            file.update(|ledger: &mut BudgetLedger| {
+                ledger.record(&session_key(&input.transcript_path), cost, today)
+            })
+            .ok()
+        }),
+        None => file.and_then(|file| file.load()),
+    }
+    .unwrap_or_default();
+
+    let mut statuses = Vec::new();
+    if let Some(cap) = config.daily {
+        let (from, to) = SpendPeriod::Today.range(today);
+        statuses.push(BudgetStatus {
+            kind: BudgetKind::Daily,
+            spent: ledger.spent_between(from, to),
+            cap,
+        });
+    }
+    if let Some(cap) = config.weekly {
+        let (from, to) = SpendPeriod::Week.range(today);
+        statuses.push(BudgetStatus {
+            kind: BudgetKind::Weekly,
+            spent: ledger.spent_between(from, to),
+            cap,
+        });
+    }
+    if let (Some(cap), Some(spent)) = (config.session, session_cost) {
+        statuses.push(BudgetStatus {
+            kind: BudgetKind::Session,
+            spent,
+            cap,
+        });
+    }
+    statuses
+}
+
+/// Color a cost-related segment by the cap closest to (or furthest past) its
+/// limit and, with `show_budget`, note how much of that cap is used
+pub fn apply_budget(
+    config: &BudgetConfig,
+    statuses: &[BudgetStatus],
+    segment_config: &mut SegmentConfig,
+    data: &mut SegmentData,
+) {
+    if !matches!(
+        segment_config.id,
+        SegmentId::Cost | SegmentId::Spend | SegmentId::BurnRate
+    ) {
+        return;
+    }
+    let Some(status) = statuses
+        .iter()
+        .max_by(|a, b| a.ratio().total_cmp(&b.ratio()))
+    else {
+        return;
+    };
+
+    let level = status.level(config);
+    let percent = (status.ratio() * 100.0).round();
+    data.metadata
+        .insert("budget_kind".to_string(), status.kind.label().to_string());
+    data.metadata
+        .insert("budget_percent".to_string(), percent.to_string());
+    data.metadata.insert(
+        "budget_level".to_string(),
+        match level {
+            BudgetLevel::Ok => "ok",
+            BudgetLevel::Warn => "warn",
+            BudgetLevel::Over => "over",
+        }
+        .to_string(),
+    );
+
+    let color = match level {
+        BudgetLevel::Ok => None,
+        BudgetLevel::Warn => Some(&config.warn_color),
+        BudgetLevel::Over => Some(&config.over_color),
+    };
+    if let Some(color) = color {
+        segment_config.colors.icon = Some(color.clone());
+        segment_config.colors.text = Some(color.clone());
+    }
+
+    let show_budget = segment_config
+        .options
+        .get("show_budget")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if show_budget {
+        let note = format!("{}% of {} budget", percent, status.kind.label());
+        data.secondary = if data.secondary.is_empty() {
+            format!("· {}", note)
+        } else {
+            format!("{} · {}", data.secondary, note)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    #[test]
+    fn spend_after_midnight_counts_toward_the_new_day() {
+        let mut ledger = BudgetLedger::default();
+        ledger.record("a", 2.0, day(9));
+        ledger.record("a", 5.0, day(10));
+
+        assert_eq!(ledger.spent_between(day(9), day(9)), 2.0);
+        assert_eq!(ledger.spent_between(day(10), day(10)), 3.0);
+        assert_eq!(ledger.spent_between(day(9), day(15)), 5.0);
+    }
+
+    #[test]
+    fn new_day_is_recorded_even_without_new_spend() {
+        let mut ledger = BudgetLedger::default();
+        assert!(ledger.record("a", 2.0, day(9)));
+        assert!(!ledger.record("a", 2.0, day(9)));
+        assert!(ledger.record("a", 2.0, day(10)));
+
+        assert_eq!(ledger.sessions["a"].last_seen, day(10));
+        assert_eq!(ledger.spent_between(day(10), day(10)), 0.0);
+    }
+
+    #[test]
+    fn lower_cost_starts_a_new_counter() {
+        let mut ledger = BudgetLedger::default();
+        ledger.record("a", 5.0, day(9));
+        ledger.record("a", 1.5, day(10));
+
+        assert_eq!(ledger.spent_between(day(10), day(10)), 1.5);
+        assert_eq!(ledger.spent_between(day(1), day(31)), 6.5);
+    }
+
+    #[test]
+    fn idle_sessions_are_dropped_after_retention() {
+        let mut ledger = BudgetLedger::default();
+        ledger.record("old", 1.0, day(1));
+        let later = day(1) + chrono::Duration::days(LEDGER_RETENTION_DAYS + 1);
+        assert!(ledger.record("new", 1.0, later));
+
+        assert!(!ledger.sessions.contains_key("old"));
+        assert!(ledger.sessions.contains_key("new"));
+    }
+}
@@ -1,4 +1,8 @@
+pub mod bar;
+pub mod budget;
 pub mod segments;
+pub mod spend;
 pub mod statusline;
+pub mod transcript;
 
 pub use statusline::{collect_all_segments, StatusLineGenerator};
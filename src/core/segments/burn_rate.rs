@@ -0,0 +1,426 @@
+use super::{Segment, SegmentData, UsageSegment};
+use crate::config::{InputData, ModelConfig, SegmentId};
+use crate::core::transcript::{Transcript, TranscriptReader};
+use crate::utils::state_file::StateFile;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 5-hour utilization readings older than this are not kept
+const MAX_SAMPLE_AGE_HOURS: i64 = 5;
+
+/// Utilization readings used to estimate how fast the 5-hour window fills
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UtilizationSamples {
+    samples: Vec<(DateTime<Utc>, f64)>,
+}
+
+impl UtilizationSamples {
+    fn file() -> Option<StateFile> {
+        StateFile::in_state_dir(".burn_rate_samples.json")
+    }
+
+    /// Record a reading; a drop means the window reset and older readings no
+    /// longer apply. Returns whether anything changed.
+    fn record(&mut self, now: DateTime<Utc>, utilization: f64) -> bool {
+        let before = self.samples.len();
+        if self
+            .samples
+            .last()
+            .is_some_and(|&(_, last)| utilization < last)
+        {
+            self.samples.clear();
+        }
+        let cutoff = now - Duration::hours(MAX_SAMPLE_AGE_HOURS);
+        self.samples.retain(|&(at, _)| at >= cutoff);
+        let pruned = self.samples.len() != before;
+        if self.samples.last().map(|&(_, last)| last) == Some(utilization) {
+            return pruned;
+        }
+        self.samples.push((now, utilization));
+        true
+    }
+
+    /// Utilization percentage points per minute over the window
+    fn rate(&self, now: DateTime<Utc>, window: Duration) -> Option<f64> {
+        let start = now - window;
+        // The reading in effect when the window opened, else the first one inside it
+        let first = match self.samples.iter().rposition(|&(at, _)| at < start) {
+            Some(index) => (start, self.samples[index].1),
+            None => *self.samples.first()?,
+        };
+        let last = self.samples.last()?;
+        let minutes = (now - first.0).num_seconds() as f64 / 60.0;
+        if minutes <= 0.0 || last.1 <= first.1 {
+            return None;
+        }
+        Some((last.1 - first.1) / minutes)
+    }
+}
+
+pub struct BurnRateSegment {
+    window: Duration,
+    show_cost: bool,
+}
+
+impl Default for BurnRateSegment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BurnRateSegment {
+    pub fn new() -> Self {
+        Self {
+            window: Duration::minutes(15),
+            show_cost: true,
+        }
+    }
+
+    /// Sliding window the rates are averaged over
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn with_cost(mut self, show_cost: bool) -> Self {
+        self.show_cost = show_cost;
+        self
+    }
+
+    fn format_tokens(tokens: f64) -> String {
+        if tokens >= 1_000_000.0 {
+            format!("{:.1}M", tokens / 1_000_000.0)
+        } else if tokens >= 1000.0 {
+            format!("{:.1}k", tokens / 1000.0)
+        } else {
+            format!("{:.0}", tokens)
+        }
+    }
+
+    fn format_minutes(minutes: f64) -> String {
+        let minutes = minutes.round() as u64;
+        if minutes < 1 {
+            "<1m".to_string()
+        } else if minutes < 60 {
+            format!("{}m", minutes)
+        } else if minutes < 48 * 60 {
+            let (hours, rest) = (minutes / 60, minutes % 60);
+            if rest == 0 {
+                format!("{}h", hours)
+            } else {
+                format!("{}h{}m", hours, rest)
+            }
+        } else {
+            format!("{}d", minutes / (24 * 60))
+        }
+    }
+
+    /// Tokens and priced cost per minute across all turns inside the window
+    ///
+    /// Cache reads are left out of the token rate: every turn re-reads the
+    /// whole cached context, so counting them tracks context size rather than
+    /// how fast new tokens are being processed.
+    fn window_rates(
+        &self,
+        transcript: &Transcript,
+        now: DateTime<Utc>,
+        model_config: &ModelConfig,
+        default_model: &str,
+    ) -> Option<(f64, Option<f64>)> {
+        let start = now - self.window;
+        let mut tokens = 0u64;
+        let mut cost: Option<f64> = None;
+        let mut first = None;
+        for turn in transcript.turns() {
+            let Some(at) = turn.timestamp.filter(|&at| at >= start) else {
+                continue;
+            };
+            first.get_or_insert(at);
+            tokens += turn.usage.input_tokens as u64
+                + turn.usage.cache_creation_input_tokens as u64
+                + turn.usage.output_tokens as u64;
+            let model_id = turn.model.as_deref().unwrap_or(default_model);
+            if let Some(pricing) = model_config.get_pricing(model_id) {
+                *cost.get_or_insert(0.0) += pricing.cost(&turn.usage.normalized());
+            }
+        }
+
+        // Average over the active span, but never less than a minute
+        let minutes = ((now - first?).num_seconds() as f64 / 60.0).max(1.0);
+        Some((tokens as f64 / minutes, cost.map(|cost| cost / minutes)))
+    }
+
+    /// Minutes until context reaches the auto-compact point at the current growth
+    fn context_minutes_left(
+        &self,
+        transcript: &Transcript,
+        now: DateTime<Utc>,
+        threshold_tokens: f64,
+    ) -> Option<f64> {
+        let start = now - self.window;
+        let recent: Vec<(DateTime<Utc>, u32)> = transcript
+            .turns_since_compaction()
+            .iter()
+            .filter(|t| !t.sidechain)
+            .filter_map(|t| {
+                Some((
+                    t.timestamp.filter(|&at| at >= start)?,
+                    t.usage.context_tokens(),
+                ))
+            })
+            .collect();
+
+        let (first_at, first_tokens) = *recent.first()?;
+        let (_, last_tokens) = *recent.last()?;
+        let minutes = (now - first_at).num_seconds() as f64 / 60.0;
+        if minutes <= 0.0 || last_tokens <= first_tokens {
+            return None;
+        }
+
+        let growth = (last_tokens - first_tokens) as f64 / minutes;
+        Some(((threshold_tokens - last_tokens as f64) / growth).max(0.0))
+    }
+}
+
+impl Segment for BurnRateSegment {
+    fn collect(&self, input: &InputData) -> Option<SegmentData> {
+        let transcript = TranscriptReader::load(&input.transcript_path)?;
+        let now = Utc::now();
+
+        let model_config = ModelConfig::load();
+        let (tokens_per_minute, cost_per_minute) = self
+            .window_rates(&transcript, now, model_config, &input.model.id)
+            .unwrap_or((0.0, None));
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "window_minutes".to_string(),
+            self.window.num_minutes().to_string(),
+        );
+        metadata.insert(
+            "tokens_per_minute".to_string(),
+            format!("{:.0}", tokens_per_minute),
+        );
+
+        let mut secondary_parts = vec![format!("{}/min", Self::format_tokens(tokens_per_minute))];
+
+        // Without pricing, charge window tokens at the session's average reported cost per token
+        let reported_cost = input.cost.as_ref().and_then(|c| c.total_cost_usd);
+        let cost_per_hour = cost_per_minute.map(|cost| cost * 60.0).or_else(|| {
+            let cost = reported_cost?;
+            let session_tokens: u64 = TranscriptReader::load_complete(&input.transcript_path)?
+                .usage_by_model()
+                .values()
+                .map(|usage| usage.input_tokens + usage.cache_creation_tokens + usage.output_tokens)
+                .sum();
+            if session_tokens == 0 {
+                return None;
+            }
+            Some(cost / session_tokens as f64 * tokens_per_minute * 60.0)
+        });
+        if let Some(cost_per_hour) = cost_per_hour {
+            metadata.insert("cost_per_hour".to_string(), format!("{:.4}", cost_per_hour));
+            if self.show_cost {
+                secondary_parts.push(format!("${:.2}/h", cost_per_hour));
+            }
+        }
+
+        let threshold_tokens = model_config.get_compact_threshold_tokens(&input.model.id) as f64;
+        let context_left = self.context_minutes_left(&transcript, now, threshold_tokens);
+        if let Some(minutes) = context_left {
+            metadata.insert(
+                "context_minutes_left".to_string(),
+                format!("{:.0}", minutes),
+            );
+        }
+
+        let five_hour_left = UsageSegment::cached_five_hour_utilization().and_then(|utilization| {
+            let samples = UtilizationSamples::file()?
+                .update(|samples: &mut UtilizationSamples| samples.record(now, utilization))
+                .ok()?;
+            let rate = samples.rate(now, self.window)?;
+            Some((100.0 - utilization).max(0.0) / rate)
+        });
+        if let Some(minutes) = five_hour_left {
+            metadata.insert(
+                "five_hour_minutes_left".to_string(),
+                format!("{:.0}", minutes),
+            );
+        }
+
+        // Whichever limit is reached first
+        let soonest = match (context_left, five_hour_left) {
+            (Some(c), Some(f)) if f < c => Some((f, "5h")),
+            (Some(c), _) => Some((c, "context")),
+            (None, Some(f)) => Some((f, "5h")),
+            (None, None) => None,
+        };
+
+        let primary = match soonest {
+            Some((minutes, limit)) => {
+                metadata.insert("limit".to_string(), limit.to_string());
+                secondary_parts.insert(0, limit.to_string());
+                format!("~{} left", Self::format_minutes(minutes))
+            }
+            None => secondary_parts.remove(0),
+        };
+
+        let secondary = if secondary_parts.is_empty() {
+            String::new()
+        } else {
+            format!("· {}", secondary_parts.join(" · "))
+        };
+
+        Some(SegmentData {
+            primary,
+            secondary,
+            metadata,
+        })
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::BurnRate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::transcript::testing::{assistant, assistant_with_usage, at, transcript};
+    use serde_json::json;
+
+    fn segment(window_minutes: i64) -> BurnRateSegment {
+        BurnRateSegment::new().with_window(Duration::minutes(window_minutes))
+    }
+
+    #[test]
+    fn token_rate_leaves_out_cache_reads() {
+        let transcript = transcript(&[
+            // Before the window
+            assistant("msg_0", 60, 1_000_000, &[]),
+            assistant_with_usage(
+                "msg_1",
+                70,
+                json!({
+                    "input_tokens": 100,
+                    "cache_creation_input_tokens": 200,
+                    "cache_read_input_tokens": 100_000,
+                    "output_tokens": 50,
+                }),
+                &[],
+            ),
+            assistant_with_usage(
+                "msg_2",
+                75,
+                json!({
+                    "input_tokens": 100,
+                    "cache_read_input_tokens": 90_000,
+                    "output_tokens": 50,
+                }),
+                &[],
+            ),
+        ]);
+
+        let (tokens, cost) = segment(15)
+            .window_rates(&transcript, at(80), &ModelConfig::default(), "unused")
+            .unwrap();
+        // 500 tokens over the 10 minutes since the first turn in the window
+        assert_eq!(tokens, 50.0);
+        // Cost still includes the cache reads: Sonnet at $3 in, $15 out,
+        // $3.75 cache write and $0.30 cache read per million
+        let expected = (0.0318 + 0.02805) / 10.0;
+        assert!((cost.unwrap() - expected).abs() < 1e-9, "{:?}", cost);
+    }
+
+    #[test]
+    fn rates_average_over_at_least_a_minute() {
+        let transcript = transcript(&[assistant("msg_1", 80, 600, &[])]);
+        let (tokens, _) = segment(15)
+            .window_rates(&transcript, at(80), &ModelConfig::default(), "unused")
+            .unwrap();
+        assert_eq!(tokens, 610.0);
+    }
+
+    #[test]
+    fn no_rates_without_turns_in_the_window() {
+        let transcript = transcript(&[assistant("msg_1", 10, 600, &[])]);
+        let rates =
+            segment(15).window_rates(&transcript, at(80), &ModelConfig::default(), "unused");
+        assert_eq!(rates, None);
+    }
+
+    #[test]
+    fn context_projection_follows_growth_in_the_window() {
+        let transcript = transcript(&[
+            assistant("msg_0", 50, 5_000, &[]),
+            assistant("msg_1", 70, 20_000, &[]),
+            assistant("msg_2", 80, 40_000, &[]),
+        ]);
+        // 20k tokens over 10 minutes leaves 60k to go at 2k per minute
+        // (context includes each turn's 10 output tokens)
+        let left = segment(15).context_minutes_left(&transcript, at(80), 100_010.0);
+        assert_eq!(left, Some(30.0));
+
+        // Already past the threshold
+        let left = segment(15).context_minutes_left(&transcript, at(80), 30_000.0);
+        assert_eq!(left, Some(0.0));
+    }
+
+    #[test]
+    fn no_context_projection_without_growth() {
+        let flat = transcript(&[
+            assistant("msg_1", 70, 40_000, &[]),
+            assistant("msg_2", 80, 40_000, &[]),
+        ]);
+        assert_eq!(
+            segment(15).context_minutes_left(&flat, at(80), 100_000.0),
+            None
+        );
+
+        let idle = transcript(&[assistant("msg_1", 10, 40_000, &[])]);
+        assert_eq!(
+            segment(15).context_minutes_left(&idle, at(80), 100_000.0),
+            None
+        );
+    }
+
+    #[test]
+    fn utilization_rate_starts_from_the_reading_in_effect_at_the_window_start() {
+        let mut samples = UtilizationSamples::default();
+        assert!(samples.record(at(60), 10.0));
+        assert!(samples.record(at(70), 20.0));
+
+        // Window covers both readings: 10 points over 10 minutes
+        assert_eq!(samples.rate(at(70), Duration::minutes(15)), Some(1.0));
+        // Window opens at minute 65, when utilization was still 10%
+        assert_eq!(samples.rate(at(70), Duration::minutes(5)), Some(2.0));
+    }
+
+    #[test]
+    fn no_utilization_rate_without_samples_or_growth() {
+        let mut samples = UtilizationSamples::default();
+        assert_eq!(samples.rate(at(70), Duration::minutes(15)), None);
+
+        samples.record(at(60), 10.0);
+        assert!(!samples.record(at(70), 10.0));
+        assert_eq!(samples.rate(at(70), Duration::minutes(15)), None);
+    }
+
+    #[test]
+    fn utilization_drop_starts_a_new_window() {
+        let mut samples = UtilizationSamples::default();
+        samples.record(at(60), 80.0);
+        assert!(samples.record(at(70), 5.0));
+        assert_eq!(samples.samples, [(at(70), 5.0)]);
+    }
+
+    #[test]
+    fn minutes_are_shown_in_the_largest_fitting_unit() {
+        assert_eq!(BurnRateSegment::format_minutes(0.4), "<1m");
+        assert_eq!(BurnRateSegment::format_minutes(45.0), "45m");
+        assert_eq!(BurnRateSegment::format_minutes(120.0), "2h");
+        assert_eq!(BurnRateSegment::format_minutes(125.0), "2h5m");
+        assert_eq!(BurnRateSegment::format_minutes(3.0 * 24.0 * 60.0), "3d");
+    }
+}
@@ -0,0 +1,113 @@
+use super::{Segment, SegmentData};
+use crate::config::{InputData, ModelConfig, NormalizedUsage, SegmentId};
+use crate::core::transcript::TranscriptReader;
+use std::collections::HashMap;
+
+pub struct CacheEfficiencySegment {
+    show_savings: bool,
+}
+
+impl Default for CacheEfficiencySegment {
+    fn default() -> Self {
+        Self { show_savings: true }
+    }
+}
+
+impl CacheEfficiencySegment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_savings(mut self, show_savings: bool) -> Self {
+        self.show_savings = show_savings;
+        self
+    }
+
+    /// Share of prompt tokens served from the cache, as a percentage
+    fn hit_percent(cache_read: u64, prompt: u64) -> Option<f64> {
+        if prompt == 0 {
+            return None;
+        }
+        Some(cache_read as f64 / prompt as f64 * 100.0)
+    }
+
+    fn prompt_tokens(usage: &NormalizedUsage) -> u64 {
+        usage.input_tokens as u64
+            + usage.cache_creation_input_tokens as u64
+            + usage.cache_read_input_tokens as u64
+    }
+
+    fn format_savings(savings: f64) -> String {
+        if savings < 0.0 {
+            format!("cache cost ${:.2}", -savings)
+        } else {
+            format!("saved ${:.2}", savings)
+        }
+    }
+}
+
+impl Segment for CacheEfficiencySegment {
+    fn collect(&self, input: &InputData) -> Option<SegmentData> {
+        let transcript = TranscriptReader::load_complete(&input.transcript_path)?;
+        let model_config = ModelConfig::load();
+
+        let mut session_read = 0u64;
+        let mut session_prompt = 0u64;
+        let mut savings = 0.0;
+        let mut priced = false;
+        for (model, usage) in transcript.usage_by_model() {
+            session_read += usage.cache_read_tokens;
+            session_prompt +=
+                usage.input_tokens + usage.cache_creation_tokens + usage.cache_read_tokens;
+
+            let model_id = if model.is_empty() {
+                &input.model.id
+            } else {
+                &model
+            };
+            if let Some(pricing) = model_config.get_pricing(model_id) {
+                savings +=
+                    pricing.cache_savings_for(usage.cache_creation_tokens, usage.cache_read_tokens);
+                priced = true;
+            }
+        }
+
+        let session_percent = Self::hit_percent(session_read, session_prompt)?;
+        let last_percent = transcript.main_turns().last().and_then(|turn| {
+            let usage = turn.usage.normalized();
+            Self::hit_percent(
+                usage.cache_read_input_tokens as u64,
+                Self::prompt_tokens(&usage),
+            )
+        });
+
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "session_hit_percent".to_string(),
+            format!("{:.1}", session_percent),
+        );
+        if let Some(percent) = last_percent {
+            metadata.insert("last_hit_percent".to_string(), format!("{:.1}", percent));
+        }
+        metadata.insert("cache_read_tokens".to_string(), session_read.to_string());
+        metadata.insert("prompt_tokens".to_string(), session_prompt.to_string());
+
+        let mut secondary_parts = vec![format!("session {:.0}%", session_percent)];
+        if priced {
+            metadata.insert("savings".to_string(), format!("{:.4}", savings));
+            if self.show_savings {
+                secondary_parts.push(Self::format_savings(savings));
+            }
+        }
+
+        Some(SegmentData {
+            primary: format!("{:.0}% hit", last_percent.unwrap_or(session_percent)),
+            secondary: format!("· {}", secondary_parts.join(" · ")),
+            metadata,
+        })
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::CacheEfficiency
+    }
+}
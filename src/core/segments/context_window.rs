@@ -1,31 +1,165 @@
 use super::{Segment, SegmentData};
-use crate::config::{InputData, ModelConfig, SegmentId, TranscriptEntry};
+use crate::config::{InputData, ModelConfig, SegmentId};
+use crate::core::transcript::{ContextSample, Transcript, TranscriptReader};
 use std::collections::HashMap;
-use std::fs;
-use std::io::{BufRead, BufReader};
-use std::path::{Path, PathBuf};
 
-#[derive(Default)]
-pub struct ContextWindowSegment;
+/// Recent turns used to estimate per-turn context growth
+const GROWTH_WINDOW: usize = 10;
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+pub struct ContextWindowSegment {
+    show_breakdown: bool,
+    show_compact_headroom: bool,
+    sparkline: Option<SparklineConfig>,
+}
+
+/// How the context history sparkline is drawn
+#[derive(Debug, Clone)]
+pub struct SparklineConfig {
+    /// Number of most recent turns shown
+    pub turns: usize,
+    /// Scale bars to the largest value shown instead of the context limit
+    pub relative: bool,
+    /// Inserted where the context was compacted
+    pub marker: String,
+}
+
+impl Default for SparklineConfig {
+    fn default() -> Self {
+        Self {
+            turns: 12,
+            relative: false,
+            marker: "│".to_string(),
+        }
+    }
+}
+
+impl Default for ContextWindowSegment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl ContextWindowSegment {
     pub fn new() -> Self {
-        Self
+        Self {
+            show_breakdown: false,
+            show_compact_headroom: false,
+            sparkline: None,
+        }
     }
 
-    /// Get context limit for the specified model
-    fn get_context_limit_for_model(model_id: &str) -> u32 {
-        let model_config = ModelConfig::load();
-        model_config.get_context_limit(model_id)
+    /// Show input / cache-read / cache-write / output token components
+    pub fn with_breakdown(mut self, show_breakdown: bool) -> Self {
+        self.show_breakdown = show_breakdown;
+        self
+    }
+
+    /// Show how much room (and roughly how many turns) remain before auto-compact
+    pub fn with_compact_headroom(mut self, show_compact_headroom: bool) -> Self {
+        self.show_compact_headroom = show_compact_headroom;
+        self
+    }
+
+    /// Show a sparkline of context size over recent turns
+    pub fn with_sparkline(mut self, sparkline: Option<SparklineConfig>) -> Self {
+        self.sparkline = sparkline;
+        self
+    }
+
+    fn format_tokens(tokens: u32) -> String {
+        if tokens >= 1000 {
+            let k_value = tokens as f64 / 1000.0;
+            if k_value.fract() == 0.0 {
+                format!("{}k", k_value as u32)
+            } else {
+                format!("{:.1}k", k_value)
+            }
+        } else {
+            tokens.to_string()
+        }
+    }
+
+    /// Average context growth per main-conversation turn since the last compaction
+    fn average_growth(transcript: &Transcript) -> Option<f64> {
+        let contexts: Vec<u32> = transcript
+            .turns_since_compaction()
+            .iter()
+            .filter(|t| !t.sidechain)
+            .map(|t| t.usage.context_tokens())
+            .collect();
+
+        let recent = &contexts[contexts.len().saturating_sub(GROWTH_WINDOW + 1)..];
+        if recent.len() < 2 {
+            return None;
+        }
+
+        let first = *recent.first()? as f64;
+        let last = *recent.last()? as f64;
+        let growth = (last - first) / (recent.len() - 1) as f64;
+        if growth > 0.0 {
+            Some(growth)
+        } else {
+            None
+        }
+    }
+
+    /// Whole turns that fit in `headroom` at the recent growth rate
+    fn turns_until_compact(transcript: &Transcript, headroom: u32) -> Option<u64> {
+        Self::average_growth(transcript).map(|growth| (headroom as f64 / growth).floor() as u64)
+    }
+
+    fn render_sparkline(
+        config: &SparklineConfig,
+        transcript: &Transcript,
+        context_limit: u32,
+    ) -> Option<String> {
+        let history = transcript.context_history();
+        let recent: &[ContextSample] = &history[history.len().saturating_sub(config.turns)..];
+        if recent.is_empty() {
+            return None;
+        }
+
+        let scale = if config.relative {
+            recent.iter().map(|s| s.tokens).max().unwrap_or(0)
+        } else {
+            context_limit
+        }
+        .max(1) as f64;
+
+        let mut line = String::new();
+        for sample in recent {
+            if sample.after_compaction {
+                line.push_str(&config.marker);
+            }
+            let ratio = (sample.tokens as f64 / scale).clamp(0.0, 1.0);
+            let level = (ratio * (SPARK_LEVELS.len() - 1) as f64).round() as usize;
+            line.push(SPARK_LEVELS[level]);
+        }
+
+        // A compaction after the last turn has no sample to carry its marker
+        if transcript
+            .compactions()
+            .last()
+            .is_some_and(|c| c.turn_index >= transcript.turn_count())
+        {
+            line.push_str(&config.marker);
+        }
+
+        Some(line)
     }
 }
 
 impl Segment for ContextWindowSegment {
     fn collect(&self, input: &InputData) -> Option<SegmentData> {
         // Dynamically determine context limit based on current model ID
-        let context_limit = Self::get_context_limit_for_model(&input.model.id);
+        let model_config = ModelConfig::load();
+        let context_limit = model_config.get_context_limit(&input.model.id);
+        let threshold_tokens = model_config.get_compact_threshold_tokens(&input.model.id);
 
-        let context_used_token_opt = parse_transcript_usage(&input.transcript_path);
+        let usage = TranscriptReader::context_usage(&input.transcript_path);
+        let context_used_token_opt = usage.map(|usage| usage.context_tokens());
 
         let (percentage_display, tokens_display) = match context_used_token_opt {
             Some(context_used_token) => {
@@ -37,18 +171,7 @@ impl Segment for ContextWindowSegment {
                     format!("{:.1}%", context_used_rate)
                 };
 
-                let tokens = if context_used_token >= 1000 {
-                    let k_value = context_used_token as f64 / 1000.0;
-                    if k_value.fract() == 0.0 {
-                        format!("{}k", k_value as u32)
-                    } else {
-                        format!("{:.1}k", k_value)
-                    }
-                } else {
-                    context_used_token.to_string()
-                };
-
-                (percentage, tokens)
+                (percentage, Self::format_tokens(context_used_token))
             }
             None => {
                 // No usage data available
@@ -71,202 +194,194 @@ impl Segment for ContextWindowSegment {
         metadata.insert("limit".to_string(), context_limit.to_string());
         metadata.insert("model".to_string(), input.model.id.clone());
 
-        Some(SegmentData {
-            primary: format!("{} · {} tokens", percentage_display, tokens_display),
-            secondary: String::new(),
-            metadata,
-        })
-    }
-
-    fn id(&self) -> SegmentId {
-        SegmentId::ContextWindow
-    }
-}
+        let mut secondary_parts = Vec::new();
+        let transcript = TranscriptReader::load(&input.transcript_path);
 
-fn parse_transcript_usage<P: AsRef<Path>>(transcript_path: P) -> Option<u32> {
-    let path = transcript_path.as_ref();
-
-    // Try to parse from current transcript file
-    if let Some(usage) = try_parse_transcript_file(path) {
-        return Some(usage);
-    }
-
-    // If file doesn't exist, try to find usage from project history
-    if !path.exists() {
-        if let Some(usage) = try_find_usage_from_project_history(path) {
-            return Some(usage);
+        if let Some(sparkline) = self
+            .sparkline
+            .as_ref()
+            .and_then(|config| Self::render_sparkline(config, transcript.as_ref()?, context_limit))
+        {
+            metadata.insert("sparkline".to_string(), sparkline.clone());
+            secondary_parts.push(sparkline);
         }
-    }
-
-    None
-}
 
-fn try_parse_transcript_file(path: &Path) -> Option<u32> {
-    let file = fs::File::open(path).ok()?;
-    let reader = BufReader::new(file);
-    let lines: Vec<String> = reader
-        .lines()
-        .collect::<Result<Vec<_>, _>>()
-        .unwrap_or_default();
-
-    if lines.is_empty() {
-        return None;
-    }
-
-    // Check if the last line is a summary
-    let last_line = lines.last()?.trim();
-    if let Ok(entry) = serde_json::from_str::<TranscriptEntry>(last_line) {
-        if entry.r#type.as_deref() == Some("summary") {
-            // Handle summary case: find usage by leafUuid
-            if let Some(leaf_uuid) = &entry.leaf_uuid {
-                let project_dir = path.parent()?;
-                return find_usage_by_leaf_uuid(leaf_uuid, project_dir);
+        if let Some(usage) = usage {
+            metadata.insert("input_tokens".to_string(), usage.input_tokens.to_string());
+            metadata.insert(
+                "cache_read_tokens".to_string(),
+                usage.cache_read_input_tokens.to_string(),
+            );
+            metadata.insert(
+                "cache_creation_tokens".to_string(),
+                usage.cache_creation_input_tokens.to_string(),
+            );
+            metadata.insert("output_tokens".to_string(), usage.output_tokens.to_string());
+
+            if self.show_breakdown {
+                secondary_parts.push(format!(
+                    "in {} · cr {} · cw {} · out {}",
+                    Self::format_tokens(usage.input_tokens),
+                    Self::format_tokens(usage.cache_read_input_tokens),
+                    Self::format_tokens(usage.cache_creation_input_tokens),
+                    Self::format_tokens(usage.output_tokens),
+                ));
             }
         }
-    }
 
-    // Normal case: find the last assistant message in current file
-    for line in lines.iter().rev() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
+        if let Some(max_output) = model_config.get_max_output_tokens(&input.model.id) {
+            metadata.insert("max_output_tokens".to_string(), max_output.to_string());
         }
+        metadata.insert(
+            "compact_threshold_tokens".to_string(),
+            threshold_tokens.to_string(),
+        );
+
+        if let Some(context_used_token) = context_used_token_opt {
+            let headroom = threshold_tokens.saturating_sub(context_used_token);
+            metadata.insert("compact_headroom_tokens".to_string(), headroom.to_string());
+
+            let turns_left = transcript
+                .as_ref()
+                .and_then(|t| Self::turns_until_compact(t, headroom));
+            if let Some(turns) = turns_left {
+                metadata.insert("turns_until_compact".to_string(), turns.to_string());
+            }
 
-        if let Ok(entry) = serde_json::from_str::<TranscriptEntry>(line) {
-            if entry.r#type.as_deref() == Some("assistant") {
-                if let Some(message) = &entry.message {
-                    if let Some(raw_usage) = &message.usage {
-                        let normalized = raw_usage.clone().normalize();
-                        return Some(normalized.display_tokens());
-                    }
-                }
+            if self.show_compact_headroom {
+                secondary_parts.push(match turns_left {
+                    Some(1) => "~1 turn to compact".to_string(),
+                    Some(turns) => format!("~{} turns to compact", turns),
+                    None => format!("{} to compact", Self::format_tokens(headroom)),
+                });
             }
         }
-    }
 
-    None
-}
-
-fn find_usage_by_leaf_uuid(leaf_uuid: &str, project_dir: &Path) -> Option<u32> {
-    // Search for the leafUuid across all session files in the project directory
-    let entries = fs::read_dir(project_dir).ok()?;
-
-    for entry in entries {
-        let entry = entry.ok()?;
-        let path = entry.path();
-
-        if path.extension().and_then(|s| s.to_str()) != Some("jsonl") {
-            continue;
-        }
+        let secondary = if secondary_parts.is_empty() {
+            String::new()
+        } else {
+            format!("· {}", secondary_parts.join(" · "))
+        };
 
-        if let Some(usage) = search_uuid_in_file(&path, leaf_uuid) {
-            return Some(usage);
-        }
+        Some(SegmentData {
+            primary: format!("{} · {} tokens", percentage_display, tokens_display),
+            secondary,
+            metadata,
+        })
     }
 
-    None
+    fn id(&self) -> SegmentId {
+        SegmentId::ContextWindow
+    }
 }
 
-fn search_uuid_in_file(path: &Path, target_uuid: &str) -> Option<u32> {
-    let file = fs::File::open(path).ok()?;
-    let reader = BufReader::new(file);
-    let lines: Vec<String> = reader
-        .lines()
-        .collect::<Result<Vec<_>, _>>()
-        .unwrap_or_default();
-
-    // Find the message with target_uuid
-    for line in &lines {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-
-        if let Ok(entry) = serde_json::from_str::<TranscriptEntry>(line) {
-            if let Some(uuid) = &entry.uuid {
-                if uuid == target_uuid {
-                    // Found the target message, check its type
-                    if entry.r#type.as_deref() == Some("assistant") {
-                        // Direct assistant message with usage
-                        if let Some(message) = &entry.message {
-                            if let Some(raw_usage) = &message.usage {
-                                let normalized = raw_usage.clone().normalize();
-                                return Some(normalized.display_tokens());
-                            }
-                        }
-                    } else if entry.r#type.as_deref() == Some("user") {
-                        // User message, need to find the parent assistant message
-                        if let Some(parent_uuid) = &entry.parent_uuid {
-                            return find_assistant_message_by_uuid(&lines, parent_uuid);
-                        }
-                    }
-                    break;
-                }
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::transcript::testing::{assistant, boundary, compact_summary, transcript};
+
+    fn turns(inputs: &[u32]) -> Vec<String> {
+        inputs
+            .iter()
+            .enumerate()
+            .map(|(i, &input)| assistant(&format!("msg_{}", i), i as u32, input, &[]))
+            .collect()
     }
 
-    None
-}
-
-fn find_assistant_message_by_uuid(lines: &[String], target_uuid: &str) -> Option<u32> {
-    for line in lines {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
+    fn sparkline(relative: bool, turns: usize) -> SparklineConfig {
+        SparklineConfig {
+            turns,
+            relative,
+            ..Default::default()
         }
+    }
 
-        if let Ok(entry) = serde_json::from_str::<TranscriptEntry>(line) {
-            if let Some(uuid) = &entry.uuid {
-                if uuid == target_uuid && entry.r#type.as_deref() == Some("assistant") {
-                    if let Some(message) = &entry.message {
-                        if let Some(raw_usage) = &message.usage {
-                            let normalized = raw_usage.clone().normalize();
-                            return Some(normalized.display_tokens());
-                        }
-                    }
-                }
-            }
-        }
+    #[test]
+    fn growth_counts_only_turns_since_the_last_compaction() {
+        let mut lines = turns(&[100_000, 150_000]);
+        lines.extend([boundary(), compact_summary()]);
+        lines.extend(turns(&[20_000, 30_000, 40_000]).into_iter().map(|line| {
+            // Distinct ids from the turns before the compaction
+            line.replace("msg_", "after_")
+        }));
+        let transcript = transcript(&lines);
+
+        assert_eq!(
+            ContextWindowSegment::average_growth(&transcript),
+            Some(10_000.0)
+        );
+        assert_eq!(
+            ContextWindowSegment::turns_until_compact(&transcript, 45_000),
+            Some(4)
+        );
     }
 
-    None
-}
+    #[test]
+    fn growth_uses_the_recent_window() {
+        // Fast early growth falls out of the last GROWTH_WINDOW turns
+        let mut inputs = vec![1_000, 50_000];
+        inputs.extend((0..=GROWTH_WINDOW as u32).map(|i| 60_000 + i * 1_000));
+        let transcript = transcript(&turns(&inputs));
+
+        assert_eq!(
+            ContextWindowSegment::average_growth(&transcript),
+            Some(1_000.0)
+        );
+    }
 
-fn try_find_usage_from_project_history(transcript_path: &Path) -> Option<u32> {
-    let project_dir = transcript_path.parent()?;
+    #[test]
+    fn no_estimate_without_growth() {
+        let single = transcript(&turns(&[10_000]));
+        assert_eq!(ContextWindowSegment::average_growth(&single), None);
+
+        let shrinking = transcript(&turns(&[30_000, 20_000]));
+        assert_eq!(ContextWindowSegment::average_growth(&shrinking), None);
+        assert_eq!(
+            ContextWindowSegment::turns_until_compact(&shrinking, 50_000),
+            None
+        );
+    }
 
-    // Find the most recent session file in the project directory
-    let mut session_files: Vec<PathBuf> = Vec::new();
-    let entries = fs::read_dir(project_dir).ok()?;
+    #[test]
+    fn sparkline_scales_to_the_limit_or_the_largest_value() {
+        let transcript = transcript(&turns(&[10_000, 20_000, 40_000]));
 
-    for entry in entries {
-        let entry = entry.ok()?;
-        let path = entry.path();
+        let absolute =
+            ContextWindowSegment::render_sparkline(&sparkline(false, 12), &transcript, 200_000);
+        assert_eq!(absolute.as_deref(), Some("▁▂▂"));
 
-        if path.extension().and_then(|s| s.to_str()) == Some("jsonl") {
-            session_files.push(path);
-        }
+        let relative =
+            ContextWindowSegment::render_sparkline(&sparkline(true, 12), &transcript, 200_000);
+        assert_eq!(relative.as_deref(), Some("▃▅█"));
     }
 
-    if session_files.is_empty() {
-        return None;
+    #[test]
+    fn sparkline_shows_only_the_most_recent_turns() {
+        let transcript = transcript(&turns(&[10_000, 20_000, 40_000]));
+        let line =
+            ContextWindowSegment::render_sparkline(&sparkline(true, 2), &transcript, 200_000);
+        assert_eq!(line.as_deref(), Some("▅█"));
     }
 
-    // Sort by modification time (most recent first)
-    session_files.sort_by_key(|path| {
-        fs::metadata(path)
-            .and_then(|m| m.modified())
-            .unwrap_or(std::time::UNIX_EPOCH)
-    });
-    session_files.reverse();
-
-    // Try to find usage from the most recent session
-    for session_path in &session_files {
-        if let Some(usage) = try_parse_transcript_file(session_path) {
-            return Some(usage);
-        }
+    #[test]
+    fn sparkline_marks_compactions() {
+        let mut lines = turns(&[10_000]);
+        lines.extend([boundary(), compact_summary()]);
+        lines.push(assistant("after_0", 5, 20_000, &[]));
+        // A compaction after the last turn is marked at the end
+        lines.push(boundary());
+        let transcript = transcript(&lines);
+
+        let line =
+            ContextWindowSegment::render_sparkline(&sparkline(false, 12), &transcript, 80_000);
+        assert_eq!(line.as_deref(), Some("▂│▃│"));
     }
 
-    None
+    #[test]
+    fn no_sparkline_without_turns() {
+        let transcript = transcript(&[boundary()]);
+        let line =
+            ContextWindowSegment::render_sparkline(&sparkline(false, 12), &transcript, 80_000);
+        assert_eq!(line, None);
+    }
 }
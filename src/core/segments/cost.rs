@@ -1,38 +1,130 @@
 use super::{Segment, SegmentData};
-use crate::config::{InputData, SegmentId};
+use crate::config::{InputData, ModelConfig, SegmentId};
+use crate::core::transcript::TranscriptReader;
 use std::collections::HashMap;
 
+/// Which session cost figure the segment shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CostSource {
+    /// Reported cost, or the computed one when Claude Code reports none
+    #[default]
+    Auto,
+    Reported,
+    /// Computed from transcript usage and models.toml pricing
+    Computed,
+    /// Reported cost with the computed one alongside
+    Both,
+}
+
+impl CostSource {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "auto" => Some(Self::Auto),
+            "reported" => Some(Self::Reported),
+            "computed" => Some(Self::Computed),
+            "both" => Some(Self::Both),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Default)]
-pub struct CostSegment;
+pub struct CostSegment {
+    source: CostSource,
+}
 
 impl CostSegment {
     pub fn new() -> Self {
-        Self
+        Self::default()
     }
-}
 
-impl Segment for CostSegment {
-    fn collect(&self, input: &InputData) -> Option<SegmentData> {
-        let cost_data = input.cost.as_ref()?;
+    pub fn with_source(mut self, source: CostSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    fn format_cost(cost: f64) -> String {
+        if cost == 0.0 || cost < 0.01 {
+            "$0".to_string()
+        } else {
+            format!("${:.2}", cost)
+        }
+    }
+
+    /// Session cost from every priced turn in the transcript, and the number of
+    /// turns whose model has no known pricing
+    fn computed_cost(input: &InputData) -> Option<(f64, u64)> {
+        let transcript = TranscriptReader::load_complete(&input.transcript_path)?;
+        let model_config = ModelConfig::load();
 
-        // Primary display: total cost
-        let primary = if let Some(cost) = cost_data.total_cost_usd {
-            if cost == 0.0 || cost < 0.01 {
-                "$0".to_string()
+        let mut cost = 0.0;
+        let mut priced = 0;
+        let mut unpriced = 0;
+        for (model, usage) in transcript.usage_by_model() {
+            let model_id = if model.is_empty() {
+                &input.model.id
             } else {
-                format!("${:.2}", cost)
+                &model
+            };
+            match usage.cost(model_config, model_id) {
+                Some(model_cost) => {
+                    cost += model_cost;
+                    priced += usage.turns;
+                }
+                None => unpriced += usage.turns,
             }
-        } else {
+        }
+
+        if priced == 0 {
             return None;
-        };
+        }
+        Some((cost, unpriced))
+    }
 
-        // Secondary display: empty for cost segment
-        let secondary = String::new();
+    /// Session cost as Claude Code reports it, falling back to the computed one
+    pub fn session_cost(input: &InputData) -> Option<f64> {
+        input
+            .cost
+            .as_ref()
+            .and_then(|c| c.total_cost_usd)
+            .or_else(|| Self::computed_cost(input).map(|(cost, _)| cost))
+    }
+}
+
+impl Segment for CostSegment {
+    fn collect(&self, input: &InputData) -> Option<SegmentData> {
+        let reported = input.cost.as_ref().and_then(|c| c.total_cost_usd);
+        let computed = match (self.source, reported) {
+            (CostSource::Reported, _) | (CostSource::Auto, Some(_)) => None,
+            _ => Self::computed_cost(input),
+        };
 
         let mut metadata = HashMap::new();
-        if let Some(cost) = cost_data.total_cost_usd {
+        if let Some(cost) = reported {
             metadata.insert("cost".to_string(), cost.to_string());
         }
+        if let Some((cost, unpriced)) = computed {
+            metadata.insert("computed_cost".to_string(), cost.to_string());
+            metadata.insert("unpriced_turns".to_string(), unpriced.to_string());
+        }
+
+        let (primary, secondary) = match self.source {
+            CostSource::Reported => (Self::format_cost(reported?), String::new()),
+            CostSource::Computed => (Self::format_cost(computed?.0), String::new()),
+            CostSource::Auto => match reported {
+                Some(cost) => (Self::format_cost(cost), String::new()),
+                None => (Self::format_cost(computed?.0), String::new()),
+            },
+            CostSource::Both => match (reported, computed) {
+                (Some(reported), Some((computed, _))) => (
+                    Self::format_cost(reported),
+                    format!("· calc {}", Self::format_cost(computed)),
+                ),
+                (Some(reported), None) => (Self::format_cost(reported), String::new()),
+                (None, Some((computed, _))) => (Self::format_cost(computed), String::new()),
+                (None, None) => return None,
+            },
+        };
 
         Some(SegmentData {
             primary,
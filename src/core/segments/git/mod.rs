@@ -0,0 +1,658 @@
+mod remote;
+
+use super::{Segment, SegmentData};
+use crate::config::{AnsiColor, InputData, SegmentId};
+use crate::utils::process::{run_with_timeout, TimedOutput};
+use crate::utils::state_file::StateFile;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub use remote::{Forge, ForgeHost, RemoteInfo};
+
+/// Cache entries untouched for this long are dropped on save
+const CACHE_RETENTION_SECS: u64 = 7 * 24 * 3600;
+
+#[derive(Debug)]
+pub struct GitInfo {
+    pub branch: String,
+    pub status: GitStatus,
+    pub ahead: u32,
+    pub behind: u32,
+    pub sha: Option<String>,
+    /// Status and ahead/behind came from an outdated cache entry
+    pub stale: bool,
+    pub remote: Option<RemoteInfo>,
+    pub last_commit: Option<CommitInfo>,
+}
+
+/// HEAD commit details used for the age/author display
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    /// Committer timestamp (unix seconds)
+    pub timestamp: u64,
+    pub author_name: String,
+    /// Author email matches the local `user.email`
+    pub authored_by_me: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GitStatus {
+    Clean,
+    Dirty,
+    Conflicts,
+    /// Status could not be determined in time and nothing was cached
+    Unknown,
+}
+
+/// Which forge URL the branch name links to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkTarget {
+    Branch,
+    Compare,
+    Repo,
+}
+
+impl LinkTarget {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "compare" => LinkTarget::Compare,
+            "repo" => LinkTarget::Repo,
+            _ => LinkTarget::Branch,
+        }
+    }
+}
+
+/// Result of a git invocation that is allowed to time out
+enum GitOutcome<T> {
+    Done(T),
+    TimedOut,
+}
+
+impl<T> GitOutcome<T> {
+    /// The result, or `None` when the deadline passed first
+    fn done(self) -> Option<T> {
+        match self {
+            GitOutcome::Done(value) => Some(value),
+            GitOutcome::TimedOut => None,
+        }
+    }
+}
+
+/// git invocations of one render, all bounded by the same deadline
+struct GitRunner<'a> {
+    working_dir: &'a str,
+    deadline: Instant,
+}
+
+impl GitRunner<'_> {
+    /// Run a git subcommand with whatever is left of the deadline
+    fn run(&self, args: &[&str]) -> GitOutcome<Option<Output>> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return GitOutcome::TimedOut;
+        }
+
+        let mut command = Command::new("git");
+        command
+            .arg("--no-optional-locks")
+            .args(args)
+            .current_dir(self.working_dir);
+
+        match run_with_timeout(&mut command, remaining) {
+            Ok(TimedOutput::Completed(output)) => GitOutcome::Done(Some(output)),
+            Ok(TimedOutput::TimedOut) => GitOutcome::TimedOut,
+            Err(_) => GitOutcome::Done(None),
+        }
+    }
+
+    /// Trimmed stdout of a successful run; `None` on failure or empty output
+    fn stdout(&self, args: &[&str]) -> GitOutcome<Option<String>> {
+        match self.run(args) {
+            GitOutcome::Done(Some(output)) if output.status.success() => GitOutcome::Done(
+                String::from_utf8(output.stdout)
+                    .ok()
+                    .map(|text| text.trim().to_string())
+                    .filter(|text| !text.is_empty()),
+            ),
+            GitOutcome::Done(_) => GitOutcome::Done(None),
+            GitOutcome::TimedOut => GitOutcome::TimedOut,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GitStatusCache {
+    repos: HashMap<String, GitCacheEntry>,
+    /// Working directory -> repository key, for when not even the git dir
+    /// can be found in time
+    #[serde(default)]
+    dirs: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GitCacheEntry {
+    /// `.git/index` mtime plus HEAD contents at the time of caching
+    key: String,
+    status: GitStatus,
+    ahead: u32,
+    behind: u32,
+    cached_at: u64,
+}
+
+pub struct GitSegment {
+    show_sha: bool,
+    show_untracked: bool,
+    timeout: Duration,
+    cache_duration: u64,
+    hyperlink: Option<LinkTarget>,
+    forge_hosts: HashMap<String, ForgeHost>,
+    ticket: Option<TicketConfig>,
+    show_commit_age: bool,
+}
+
+/// Issue key extraction from branch names
+pub struct TicketConfig {
+    /// Regex applied to the branch; the first capture group (or whole match) is the key
+    pub pattern: Regex,
+    /// Link template with a `{ticket}` placeholder
+    pub url_template: Option<String>,
+    /// Show only the key instead of the full branch name
+    pub replace_branch: bool,
+    pub color: Option<AnsiColor>,
+}
+
+impl Default for GitSegment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GitSegment {
+    pub fn new() -> Self {
+        Self {
+            show_sha: false,
+            show_untracked: true,
+            timeout: Duration::from_millis(500),
+            cache_duration: 5,
+            hyperlink: None,
+            forge_hosts: HashMap::new(),
+            ticket: None,
+            show_commit_age: false,
+        }
+    }
+
+    pub fn with_sha(mut self, show_sha: bool) -> Self {
+        self.show_sha = show_sha;
+        self
+    }
+
+    /// Include untracked files in the dirty check (`-uno` when disabled)
+    pub fn with_untracked(mut self, show_untracked: bool) -> Self {
+        self.show_untracked = show_untracked;
+        self
+    }
+
+    /// Deadline shared by all git invocations of one render
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// How long (seconds) a cache entry with a matching key is reused
+    pub fn with_cache_duration(mut self, seconds: u64) -> Self {
+        self.cache_duration = seconds;
+        self
+    }
+
+    /// Wrap the branch name in an OSC 8 link to the forge
+    pub fn with_hyperlink(mut self, target: Option<LinkTarget>) -> Self {
+        self.hyperlink = target;
+        self
+    }
+
+    /// Map self-hosted host patterns (`git.corp.com`, `*.corp.com`) to a forge
+    /// and web URL
+    pub fn with_forge_hosts(mut self, forge_hosts: HashMap<String, ForgeHost>) -> Self {
+        self.forge_hosts = forge_hosts;
+        self
+    }
+
+    /// Extract an issue key from the branch name and show it as its own part
+    pub fn with_ticket(mut self, ticket: Option<TicketConfig>) -> Self {
+        self.ticket = ticket;
+        self
+    }
+
+    /// Show time since HEAD's commit and record its author
+    pub fn with_commit_age(mut self, show_commit_age: bool) -> Self {
+        self.show_commit_age = show_commit_age;
+        self
+    }
+
+    fn extract_ticket(&self, branch: &str) -> Option<(String, Option<String>)> {
+        let ticket = self.ticket.as_ref()?;
+        let captures = ticket.pattern.captures(branch)?;
+        let key = captures.get(1).or_else(|| captures.get(0))?.as_str();
+        if key.is_empty() {
+            return None;
+        }
+
+        let url = ticket
+            .url_template
+            .as_ref()
+            .map(|template| template.replace("{ticket}", key));
+        Some((key.to_string(), url))
+    }
+
+    fn get_git_info(&self, working_dir: &str) -> Option<GitInfo> {
+        let git = GitRunner {
+            working_dir,
+            deadline: Instant::now() + self.timeout,
+        };
+        let mut cache = Self::load_cache();
+
+        let git_dir = match self.get_git_dir(&git) {
+            GitOutcome::Done(git_dir) => git_dir?,
+            GitOutcome::TimedOut => return self.cached_git_info(cache, working_dir),
+        };
+        let head = Self::head_branch(&git_dir);
+
+        let repo_key = git_dir.to_string_lossy().to_string();
+        let cache_key = Self::cache_key(&git_dir);
+        let now = unix_now();
+        let cached = cache.repos.remove(&repo_key);
+
+        let fresh = cached.as_ref().filter(|entry| {
+            entry.key == cache_key && now.saturating_sub(entry.cached_at) < self.cache_duration
+        });
+        let (status, ahead, behind, stale) = match fresh {
+            Some(entry) => (entry.status, entry.ahead, entry.behind, false),
+            None => {
+                let status = self.get_status(&git);
+                let ahead_behind = match status {
+                    GitOutcome::Done(_) => self.get_ahead_behind(&git),
+                    GitOutcome::TimedOut => GitOutcome::TimedOut,
+                };
+
+                match (status, ahead_behind) {
+                    (GitOutcome::Done(status), GitOutcome::Done((ahead, behind))) => {
+                        Self::save_cache(
+                            working_dir,
+                            repo_key,
+                            GitCacheEntry {
+                                key: cache_key,
+                                status,
+                                ahead,
+                                behind,
+                                cached_at: now,
+                            },
+                            now,
+                        );
+                        (status, ahead, behind, false)
+                    }
+                    // Deadline missed: fall back to whatever we had for this repo
+                    _ => match cached {
+                        Some(entry) => (entry.status, entry.ahead, entry.behind, true),
+                        None => (GitStatus::Unknown, 0, 0, true),
+                    },
+                }
+            }
+        };
+
+        // Extras only get what is left of the deadline after the status
+        let sha = if self.show_sha {
+            self.get_sha(&git)
+        } else {
+            None
+        };
+        let last_commit = if self.show_commit_age {
+            self.get_last_commit(&git)
+        } else {
+            None
+        };
+        let remote = remote::resolve_remote(&git_dir, head.as_deref(), &self.forge_hosts);
+
+        Some(GitInfo {
+            branch: head.unwrap_or_else(|| "detached".to_string()),
+            status,
+            ahead,
+            behind,
+            sha,
+            stale,
+            remote,
+            last_commit,
+        })
+    }
+
+    /// Last cached result for a directory whose repository git could not
+    /// locate before the deadline
+    fn cached_git_info(&self, mut cache: GitStatusCache, working_dir: &str) -> Option<GitInfo> {
+        let repo_key = cache.dirs.remove(working_dir)?;
+        let entry = cache.repos.remove(&repo_key)?;
+        let git_dir = PathBuf::from(repo_key);
+        let head = Self::head_branch(&git_dir);
+        let remote = remote::resolve_remote(&git_dir, head.as_deref(), &self.forge_hosts);
+
+        Some(GitInfo {
+            branch: head.unwrap_or_else(|| "detached".to_string()),
+            status: entry.status,
+            ahead: entry.ahead,
+            behind: entry.behind,
+            sha: None,
+            stale: true,
+            remote,
+            last_commit: None,
+        })
+    }
+
+    fn get_git_dir(&self, git: &GitRunner) -> GitOutcome<Option<PathBuf>> {
+        match git.stdout(&["rev-parse", "--absolute-git-dir"]) {
+            GitOutcome::Done(dir) => GitOutcome::Done(dir.map(PathBuf::from)),
+            GitOutcome::TimedOut => GitOutcome::TimedOut,
+        }
+    }
+
+    /// Branch HEAD points at, read from the git dir without spawning git;
+    /// `None` when HEAD is detached
+    fn head_branch(git_dir: &Path) -> Option<String> {
+        let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+        head.trim()
+            .strip_prefix("ref: refs/heads/")
+            .map(|branch| branch.to_string())
+    }
+
+    fn get_status(&self, git: &GitRunner) -> GitOutcome<GitStatus> {
+        let untracked = if self.show_untracked {
+            "-unormal"
+        } else {
+            "-uno"
+        };
+
+        match git.run(&["status", "--porcelain", untracked]) {
+            GitOutcome::Done(Some(output)) if output.status.success() => {
+                let status_text = String::from_utf8(output.stdout).unwrap_or_default();
+
+                if status_text.trim().is_empty() {
+                    return GitOutcome::Done(GitStatus::Clean);
+                }
+
+                if status_text.contains("UU")
+                    || status_text.contains("AA")
+                    || status_text.contains("DD")
+                {
+                    GitOutcome::Done(GitStatus::Conflicts)
+                } else {
+                    GitOutcome::Done(GitStatus::Dirty)
+                }
+            }
+            GitOutcome::TimedOut => GitOutcome::TimedOut,
+            _ => GitOutcome::Done(GitStatus::Clean),
+        }
+    }
+
+    /// Commits ahead of and behind the upstream, in a single git call
+    fn get_ahead_behind(&self, git: &GitRunner) -> GitOutcome<(u32, u32)> {
+        match git.stdout(&["rev-list", "--left-right", "--count", "@{u}...HEAD"]) {
+            GitOutcome::Done(counts) => {
+                // "<behind>\t<ahead>"; no upstream reads as neither
+                let mut counts = counts
+                    .as_deref()
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .map(|count| count.parse().unwrap_or(0));
+                let behind = counts.next().unwrap_or(0);
+                let ahead = counts.next().unwrap_or(0);
+                GitOutcome::Done((ahead, behind))
+            }
+            GitOutcome::TimedOut => GitOutcome::TimedOut,
+        }
+    }
+
+    fn get_sha(&self, git: &GitRunner) -> Option<String> {
+        git.stdout(&["rev-parse", "--short=7", "HEAD"])
+            .done()
+            .flatten()
+    }
+
+    fn get_last_commit(&self, git: &GitRunner) -> Option<CommitInfo> {
+        let log = git
+            .stdout(&["log", "-1", "--format=%ct%n%ae%n%an"])
+            .done()
+            .flatten()?;
+        let mut lines = log.lines();
+        let timestamp = lines.next()?.trim().parse().ok()?;
+        let author_email = lines.next().unwrap_or("").trim().to_lowercase();
+        let author_name = lines.next().unwrap_or("").trim().to_string();
+
+        let user_email = git
+            .stdout(&["config", "user.email"])
+            .done()
+            .flatten()
+            .map(|e| e.to_lowercase());
+        let authored_by_me = user_email.is_some_and(|e| !e.is_empty() && e == author_email);
+
+        Some(CommitInfo {
+            timestamp,
+            author_name,
+            authored_by_me,
+        })
+    }
+
+    /// Compact relative age such as `45s`, `12m`, `3h` or `2d`
+    fn format_age(seconds: u64) -> String {
+        if seconds < 60 {
+            format!("{}s", seconds)
+        } else if seconds < 3600 {
+            format!("{}m", seconds / 60)
+        } else if seconds < 86_400 {
+            format!("{}h", seconds / 3600)
+        } else {
+            format!("{}d", seconds / 86_400)
+        }
+    }
+
+    /// Build the cache key from the index mtime and what HEAD points at
+    ///
+    /// Read straight from the git dir so a cache hit costs no extra processes.
+    fn cache_key(git_dir: &Path) -> String {
+        let index_mtime = fs::metadata(git_dir.join("index"))
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+
+        let head = fs::read_to_string(git_dir.join("HEAD")).unwrap_or_default();
+        let head = head.trim();
+
+        // Resolve a symbolic HEAD one level so new commits change the key
+        let target = head
+            .strip_prefix("ref: ")
+            .and_then(|r| fs::read_to_string(remote::common_dir(git_dir).join(r)).ok())
+            .unwrap_or_default();
+
+        format!("{}:{}:{}", index_mtime, head, target.trim())
+    }
+
+    fn cache_file() -> Option<StateFile> {
+        StateFile::in_state_dir(".git_cache.json")
+    }
+
+    fn load_cache() -> GitStatusCache {
+        Self::cache_file()
+            .and_then(|file| file.load())
+            .unwrap_or_default()
+    }
+
+    /// Store one repository's entry, merged under the lock with whatever other
+    /// sessions cached since this one loaded
+    fn save_cache(working_dir: &str, repo_key: String, entry: GitCacheEntry, now: u64) {
+        if let Some(file) = Self::cache_file() {
+            let _ = file.update(|cache: &mut GitStatusCache| {
+                cache.dirs.insert(working_dir.to_string(), repo_key.clone());
+                cache.repos.insert(repo_key, entry);
+                cache
+                    .repos
+                    .retain(|_, entry| now.saturating_sub(entry.cached_at) < CACHE_RETENTION_SECS);
+                let repos = &cache.repos;
+                cache
+                    .dirs
+                    .retain(|_, repo_key| repos.contains_key(repo_key));
+                true
+            });
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl Segment for GitSegment {
+    fn collect(&self, input: &InputData) -> Option<SegmentData> {
+        let git_info = self.get_git_info(&input.workspace.current_dir)?;
+
+        let mut metadata = HashMap::new();
+        metadata.insert("branch".to_string(), git_info.branch.clone());
+        metadata.insert("status".to_string(), format!("{:?}", git_info.status));
+        metadata.insert("ahead".to_string(), git_info.ahead.to_string());
+        metadata.insert("behind".to_string(), git_info.behind.to_string());
+        metadata.insert("stale".to_string(), git_info.stale.to_string());
+
+        if let Some(ref sha) = git_info.sha {
+            metadata.insert("sha".to_string(), sha.clone());
+        }
+
+        let now = unix_now();
+        let commit_age = git_info.last_commit.as_ref().map(|commit| {
+            let age = now.saturating_sub(commit.timestamp);
+            metadata.insert("commit_timestamp".to_string(), commit.timestamp.to_string());
+            metadata.insert("commit_age_seconds".to_string(), age.to_string());
+            metadata.insert("commit_author".to_string(), commit.author_name.clone());
+            metadata.insert(
+                "commit_by_me".to_string(),
+                commit.authored_by_me.to_string(),
+            );
+
+            // Session start is approximated from Claude Code's wall-clock duration
+            if let Some(duration_ms) = input.cost.as_ref().and_then(|c| c.total_duration_ms) {
+                let session_start = now.saturating_sub(duration_ms / 1000);
+                metadata.insert(
+                    "commit_in_session".to_string(),
+                    (commit.timestamp >= session_start).to_string(),
+                );
+            }
+
+            format!("{} ago", Self::format_age(age))
+        });
+        if let Some(ref age) = commit_age {
+            metadata.insert("commit_age".to_string(), age.clone());
+        }
+
+        if let Some(ref remote) = git_info.remote {
+            metadata.insert("remote".to_string(), remote.name.clone());
+            metadata.insert("remote_host".to_string(), remote.host.clone());
+            metadata.insert("repo".to_string(), remote.repo.clone());
+            metadata.insert("repo_url".to_string(), remote.repo_url.clone());
+            if let Some(forge) = remote.forge {
+                metadata.insert("forge".to_string(), forge.name().to_string());
+            }
+            if let Some(ref url) = remote.branch_url {
+                metadata.insert("branch_url".to_string(), url.clone());
+            }
+            if let Some(ref url) = remote.compare_url {
+                metadata.insert("compare_url".to_string(), url.clone());
+            }
+        }
+
+        let link_url = self.hyperlink.and_then(|target| {
+            let remote = git_info.remote.as_ref()?;
+            match target {
+                LinkTarget::Branch => remote.branch_url.clone(),
+                // Fall back to the branch page when there is nothing to compare
+                LinkTarget::Compare => remote
+                    .compare_url
+                    .clone()
+                    .or_else(|| remote.branch_url.clone()),
+                LinkTarget::Repo => Some(remote.repo_url.clone()),
+            }
+        });
+
+        let branch_text = match link_url {
+            Some(url) => remote::hyperlink(&git_info.branch, &url),
+            None => git_info.branch.clone(),
+        };
+
+        let primary = match (self.extract_ticket(&git_info.branch), &self.ticket) {
+            (Some((key, url)), Some(ticket)) => {
+                metadata.insert("ticket".to_string(), key.clone());
+                if let Some(ref url) = url {
+                    metadata.insert("ticket_url".to_string(), url.clone());
+                }
+
+                let mut ticket_text = match url {
+                    Some(url) => remote::hyperlink(&key, &url),
+                    None => key,
+                };
+                // Reset only the foreground so a segment background survives
+                if let Some(ref color) = ticket.color {
+                    ticket_text = format!("{}{}\x1b[39m", color.fg_sequence(), ticket_text);
+                }
+
+                if ticket.replace_branch {
+                    ticket_text
+                } else {
+                    format!("{} {}", branch_text, ticket_text)
+                }
+            }
+            _ => branch_text,
+        };
+        let mut status_parts = Vec::new();
+
+        let status_icon = match git_info.status {
+            GitStatus::Clean => "✓",
+            GitStatus::Dirty => "●",
+            GitStatus::Conflicts => "⚠",
+            GitStatus::Unknown => "?",
+        };
+        // A leading "~" marks values carried over from an outdated cache entry
+        if git_info.stale && git_info.status != GitStatus::Unknown {
+            status_parts.push(format!("~{}", status_icon));
+        } else {
+            status_parts.push(status_icon.to_string());
+        }
+
+        if git_info.ahead > 0 {
+            status_parts.push(format!("↑{}", git_info.ahead));
+        }
+        if git_info.behind > 0 {
+            status_parts.push(format!("↓{}", git_info.behind));
+        }
+
+        if let Some(ref sha) = git_info.sha {
+            status_parts.push(sha.clone());
+        }
+
+        if let Some(age) = commit_age {
+            status_parts.push(age);
+        }
+
+        Some(SegmentData {
+            primary,
+            secondary: status_parts.join(" "),
+            metadata,
+        })
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::Git
+    }
+}
@@ -0,0 +1,507 @@
+//! Remote and forge detection derived purely from files under the git dir
+//!
+//! Nothing here spawns git or touches the network: `.git/config`, the
+//! remote HEAD symref and the configured host patterns are all we need.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Forge {
+    GitHub,
+    GitLab,
+    Gitea,
+    Bitbucket,
+}
+
+impl Forge {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "github" => Some(Forge::GitHub),
+            "gitlab" => Some(Forge::GitLab),
+            "gitea" | "forgejo" => Some(Forge::Gitea),
+            "bitbucket" => Some(Forge::Bitbucket),
+            _ => None,
+        }
+    }
+
+    /// Guess the forge from well-known public hosts
+    fn from_host(host: &str) -> Option<Self> {
+        let host = host.to_lowercase();
+        if host == "github.com" || host.ends_with(".github.com") {
+            Some(Forge::GitHub)
+        } else if host == "gitlab.com" || host.starts_with("gitlab.") {
+            Some(Forge::GitLab)
+        } else if host == "bitbucket.org" || host.starts_with("bitbucket.") {
+            Some(Forge::Bitbucket)
+        } else if host == "codeberg.org" || host.starts_with("gitea.") {
+            Some(Forge::Gitea)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Forge::GitHub => "github",
+            Forge::GitLab => "gitlab",
+            Forge::Gitea => "gitea",
+            Forge::Bitbucket => "bitbucket",
+        }
+    }
+}
+
+/// Self-hosted forge entry of the `forge_hosts` option
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ForgeHost {
+    /// Forge name; guessed from the host when not given
+    pub forge: Option<String>,
+    /// Web base URL such as `http://git.example.com:8080`, for forges whose
+    /// web UI is not at `https://<host>`
+    pub web_url: Option<String>,
+}
+
+impl ForgeHost {
+    /// Either a forge name (`"gitlab"`) or a table with `forge` and/or `url`
+    pub fn from_option(value: &serde_json::Value) -> Option<Self> {
+        if let Some(forge) = value.as_str() {
+            return Some(Self {
+                forge: Some(forge.to_string()),
+                web_url: None,
+            });
+        }
+        let table = value.as_object()?;
+        let field = |name: &str| {
+            table
+                .get(name)
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string())
+        };
+        Some(Self {
+            forge: field("forge"),
+            web_url: field("url").map(|url| url.trim_end_matches('/').to_string()),
+        })
+    }
+}
+
+/// Upstream remote of the current branch with forge-specific web URLs
+#[derive(Debug, Clone)]
+pub struct RemoteInfo {
+    /// Remote name, e.g. `origin`
+    pub name: String,
+    pub host: String,
+    /// `owner/repo` path on the forge (may contain nested groups)
+    pub repo: String,
+    pub forge: Option<Forge>,
+    pub repo_url: String,
+    pub branch_url: Option<String>,
+    pub compare_url: Option<String>,
+}
+
+/// Resolve the remote for `branch` from the repository config
+///
+/// `forge_hosts` maps self-hosted host patterns (exact host or `*.suffix`)
+/// to a forge and web URL so links can be built for private instances.
+/// Branch and compare URLs are only built for a branch (`None` when HEAD is
+/// detached) that exists on the remote.
+pub fn resolve_remote(
+    git_dir: &Path,
+    branch: Option<&str>,
+    forge_hosts: &HashMap<String, ForgeHost>,
+) -> Option<RemoteInfo> {
+    let common_dir = common_dir(git_dir);
+    let config = fs::read_to_string(common_dir.join("config")).ok()?;
+    let config = parse_git_config(&config);
+
+    let branch_config = branch.and_then(|b| config.get(&format!("branch \"{}\"", b)));
+    let tracked_remote = branch_config
+        .and_then(|s| s.get("remote"))
+        .filter(|r| r.as_str() != ".");
+    let remote_branch = match tracked_remote {
+        Some(_) => branch_config
+            .and_then(|s| s.get("merge"))
+            .map(|m| m.trim_start_matches("refs/heads/").to_string()),
+        None => None,
+    }
+    .or_else(|| branch.map(|b| b.to_string()));
+
+    // Upstream remote first, then origin, then any remote at all
+    let remote_name = tracked_remote.cloned().or_else(|| {
+        if config.contains_key("remote \"origin\"") {
+            Some("origin".to_string())
+        } else {
+            config
+                .keys()
+                .filter_map(|k| k.strip_prefix("remote \"")?.strip_suffix('"'))
+                .min()
+                .map(|s| s.to_string())
+        }
+    })?;
+
+    let url = config
+        .get(&format!("remote \"{}\"", remote_name))?
+        .get("url")?;
+    let remote_url = parse_remote_url(url)?;
+
+    let forge_host = match_forge_host(&remote_url.host, forge_hosts);
+    let forge = forge_host
+        .and_then(|h| h.forge.as_deref())
+        .and_then(Forge::from_name)
+        .or_else(|| Forge::from_host(&remote_url.host));
+    let web_base = forge_host
+        .and_then(|h| h.web_url.clone())
+        .unwrap_or_else(|| remote_url.web_base());
+    let repo_url = format!("{}/{}", web_base, remote_url.repo);
+
+    // A branch that was never pushed has no page to link to
+    let remote_branch = remote_branch.filter(|b| has_remote_ref(&common_dir, &remote_name, b));
+    let default_branch = remote_default_branch(&common_dir, &remote_name);
+    let branch_url = match (forge, &remote_branch) {
+        (Some(f), Some(b)) => Some(branch_url(f, &repo_url, b)),
+        _ => None,
+    };
+    let compare_url = match (forge, default_branch, &remote_branch) {
+        (Some(f), Some(base), Some(b)) if base != *b => Some(compare_url(f, &repo_url, &base, b)),
+        _ => None,
+    };
+
+    Some(RemoteInfo {
+        name: remote_name,
+        host: remote_url.host,
+        repo: remote_url.repo,
+        forge,
+        repo_url,
+        branch_url,
+        compare_url,
+    })
+}
+
+/// Linked worktrees keep config and refs in the shared common dir
+pub fn common_dir(git_dir: &Path) -> PathBuf {
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(content) => {
+            let path = PathBuf::from(content.trim());
+            if path.is_absolute() {
+                path
+            } else {
+                git_dir.join(path)
+            }
+        }
+        Err(_) => git_dir.to_path_buf(),
+    }
+}
+
+/// Minimal `.git/config` reader: section name -> key -> last value
+///
+/// Section headers keep their subsection verbatim (`remote "origin"`), keys
+/// are lowercased like git does.
+fn parse_git_config(content: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current = String::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = match header.split_once(' ') {
+                Some((name, sub)) => format!("{} {}", name.to_lowercase(), sub.trim()),
+                None => header.to_lowercase(),
+            };
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim().trim_matches('"').to_string();
+            sections
+                .entry(current.clone())
+                .or_default()
+                .insert(key.trim().to_lowercase(), value);
+        }
+    }
+
+    sections
+}
+
+/// Parts of a remote URL needed to build web URLs
+#[derive(Debug, PartialEq)]
+struct RemoteUrl {
+    /// `http` or `https` for web remotes; `None` for ssh, git and scp-like ones
+    web_scheme: Option<String>,
+    host: String,
+    port: Option<u16>,
+    /// Repository path on the forge (may contain nested groups)
+    repo: String,
+}
+
+impl RemoteUrl {
+    /// Web root of the forge: an http(s) remote keeps its scheme and port,
+    /// anything else is assumed to be served over https on the default port
+    fn web_base(&self) -> String {
+        match (&self.web_scheme, self.port) {
+            (Some(scheme), Some(port)) => format!("{}://{}:{}", scheme, self.host, port),
+            (Some(scheme), None) => format!("{}://{}", scheme, self.host),
+            (None, _) => format!("https://{}", self.host),
+        }
+    }
+}
+
+/// Split a remote URL into scheme, host, port and repository path
+///
+/// Handles scp-like (`git@host:owner/repo.git`), `ssh://`, `git://`
+/// and `http(s)://` forms; credentials are dropped.
+fn parse_remote_url(url: &str) -> Option<RemoteUrl> {
+    let url = url.trim();
+
+    let (web_scheme, host, port, path) = if let Some((scheme, rest)) = url.split_once("://") {
+        let (authority, path) = rest.split_once('/')?;
+        let authority = authority.rsplit('@').next()?;
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+                (host, port.parse().ok())
+            }
+            _ => (authority, None),
+        };
+        let scheme = scheme.to_lowercase();
+        let web_scheme = matches!(scheme.as_str(), "http" | "https").then_some(scheme);
+        (web_scheme, host, port, path)
+    } else if let Some((authority, path)) = url.split_once(':') {
+        // scp-like syntax; a bare local path never contains a colon here
+        let host = authority.rsplit('@').next()?;
+        (None, host, None, path)
+    } else {
+        return None;
+    };
+
+    let repo = path
+        .trim_start_matches('/')
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .to_string();
+
+    if host.is_empty() || !repo.contains('/') {
+        return None;
+    }
+
+    Some(RemoteUrl {
+        web_scheme,
+        host: host.to_lowercase(),
+        port,
+        repo,
+    })
+}
+
+fn match_forge_host<'a>(
+    host: &str,
+    forge_hosts: &'a HashMap<String, ForgeHost>,
+) -> Option<&'a ForgeHost> {
+    forge_hosts.iter().find_map(|(pattern, forge_host)| {
+        let pattern = pattern.to_lowercase();
+        let matched = match pattern.strip_prefix("*.") {
+            Some(suffix) => host == suffix || host.ends_with(&format!(".{}", suffix)),
+            None => host == pattern,
+        };
+        matched.then_some(forge_host)
+    })
+}
+
+/// Whether `refs/remotes/<remote>/<branch>` exists, loose or packed
+fn has_remote_ref(common_dir: &Path, remote: &str, branch: &str) -> bool {
+    let name = format!("refs/remotes/{}/{}", remote, branch);
+    if common_dir.join(&name).is_file() {
+        return true;
+    }
+    fs::read_to_string(common_dir.join("packed-refs")).is_ok_and(|packed| {
+        packed
+            .lines()
+            .any(|line| line.split_once(' ').is_some_and(|(_, r)| r == name))
+    })
+}
+
+/// Default branch as recorded by `refs/remotes/<remote>/HEAD`
+fn remote_default_branch(common_dir: &Path, remote: &str) -> Option<String> {
+    let head = fs::read_to_string(
+        common_dir
+            .join("refs")
+            .join("remotes")
+            .join(remote)
+            .join("HEAD"),
+    )
+    .ok()?;
+    let prefix = format!("ref: refs/remotes/{}/", remote);
+    head.trim().strip_prefix(&prefix).map(|b| b.to_string())
+}
+
+fn branch_url(forge: Forge, repo_url: &str, branch: &str) -> String {
+    let branch = encode_ref(branch);
+    match forge {
+        Forge::GitHub => format!("{}/tree/{}", repo_url, branch),
+        Forge::GitLab => format!("{}/-/tree/{}", repo_url, branch),
+        Forge::Gitea => format!("{}/src/branch/{}", repo_url, branch),
+        Forge::Bitbucket => format!("{}/src/{}", repo_url, branch),
+    }
+}
+
+fn compare_url(forge: Forge, repo_url: &str, base: &str, branch: &str) -> String {
+    let (base, branch) = (encode_ref(base), encode_ref(branch));
+    match forge {
+        Forge::GitHub | Forge::Gitea => format!("{}/compare/{}...{}", repo_url, base, branch),
+        Forge::GitLab => format!("{}/-/compare/{}...{}", repo_url, base, branch),
+        Forge::Bitbucket => format!("{}/branches/compare/{}%0D{}", repo_url, branch, base),
+    }
+}
+
+/// Percent-encode characters that would break a URL path, keeping `/`
+fn encode_ref(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for ch in name.chars() {
+        match ch {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '_' | '.' | '~' | '/' => out.push(ch),
+            _ => {
+                let mut buf = [0u8; 4];
+                for byte in ch.encode_utf8(&mut buf).bytes() {
+                    out.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Wrap text in an OSC 8 hyperlink
+pub fn hyperlink(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Git dir with the given config and remote-tracking refs
+    fn git_dir(name: &str, config: &str, remote_refs: &[&str]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ccline-remote-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("config"), config).unwrap();
+        for name in remote_refs {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "0000000000000000000000000000000000000000\n").unwrap();
+        }
+        dir
+    }
+
+    const CONFIG: &str = r#"
+[remote "origin"]
+	url = git@github.com:owner/repo.git
+[branch "feature"]
+	remote = origin
+	merge = refs/heads/feature
+"#;
+
+    #[test]
+    fn web_base_keeps_scheme_and_port_of_http_remotes() {
+        let url = parse_remote_url("http://user@git.example.com:8080/group/sub/repo.git").unwrap();
+        assert_eq!(url.host, "git.example.com");
+        assert_eq!(url.repo, "group/sub/repo");
+        assert_eq!(url.web_base(), "http://git.example.com:8080");
+
+        let url = parse_remote_url("https://git.example.com/owner/repo").unwrap();
+        assert_eq!(url.web_base(), "https://git.example.com");
+    }
+
+    #[test]
+    fn ssh_remotes_link_to_https_without_their_port() {
+        let url = parse_remote_url("ssh://git@git.example.com:2222/owner/repo.git").unwrap();
+        assert_eq!(url.port, Some(2222));
+        assert_eq!(url.web_base(), "https://git.example.com");
+
+        let url = parse_remote_url("git@github.com:owner/repo.git").unwrap();
+        assert_eq!(url.web_base(), "https://github.com");
+    }
+
+    #[test]
+    fn pushed_branch_gets_branch_and_compare_urls() {
+        let dir = git_dir(
+            "pushed",
+            CONFIG,
+            &["refs/remotes/origin/feature", "refs/remotes/origin/HEAD"],
+        );
+        fs::write(
+            dir.join("refs/remotes/origin/HEAD"),
+            "ref: refs/remotes/origin/main\n",
+        )
+        .unwrap();
+
+        let remote = resolve_remote(&dir, Some("feature"), &HashMap::new()).unwrap();
+        assert_eq!(
+            remote.branch_url.as_deref(),
+            Some("https://github.com/owner/repo/tree/feature")
+        );
+        assert_eq!(
+            remote.compare_url.as_deref(),
+            Some("https://github.com/owner/repo/compare/main...feature")
+        );
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn packed_remote_ref_counts_as_pushed() {
+        let dir = git_dir("packed", CONFIG, &[]);
+        fs::write(
+            dir.join("packed-refs"),
+            "# pack-refs with: peeled fully-peeled sorted\n\
+             0000000000000000000000000000000000000000 refs/remotes/origin/feature\n",
+        )
+        .unwrap();
+
+        let remote = resolve_remote(&dir, Some("feature"), &HashMap::new()).unwrap();
+        assert!(remote.branch_url.is_some());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn detached_head_and_unpushed_branch_get_no_branch_url() {
+        let dir = git_dir("unpushed", CONFIG, &[]);
+
+        let detached = resolve_remote(&dir, None, &HashMap::new()).unwrap();
+        assert_eq!(detached.branch_url, None);
+        assert_eq!(detached.compare_url, None);
+        assert_eq!(detached.repo_url, "https://github.com/owner/repo");
+
+        let unpushed = resolve_remote(&dir, Some("feature"), &HashMap::new()).unwrap();
+        assert_eq!(unpushed.branch_url, None);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn forge_hosts_set_the_forge_and_web_url() {
+        let dir = git_dir(
+            "forge-hosts",
+            r#"
+[remote "origin"]
+	url = ssh://git@git.corp.example:2222/team/repo.git
+"#,
+            &["refs/remotes/origin/main"],
+        );
+        let value =
+            serde_json::json!({ "forge": "gitlab", "url": "http://git.corp.example:8443/" });
+        let forge_hosts = HashMap::from([(
+            "*.corp.example".to_string(),
+            ForgeHost::from_option(&value).unwrap(),
+        )]);
+
+        let remote = resolve_remote(&dir, Some("main"), &forge_hosts).unwrap();
+        assert_eq!(remote.forge, Some(Forge::GitLab));
+        assert_eq!(remote.repo_url, "http://git.corp.example:8443/team/repo");
+        assert_eq!(
+            remote.branch_url.as_deref(),
+            Some("http://git.corp.example:8443/team/repo/-/tree/main")
+        );
+        let _ = fs::remove_dir_all(dir);
+    }
+}
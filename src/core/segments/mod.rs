@@ -1,10 +1,14 @@
+pub mod burn_rate;
+pub mod cache_efficiency;
 pub mod context_window;
 pub mod cost;
 pub mod directory;
 pub mod git;
 pub mod model;
 pub mod output_style;
+pub mod plan;
 pub mod session;
+pub mod spend;
 pub mod update;
 pub mod usage;
 
@@ -25,12 +29,16 @@ pub struct SegmentData {
 }
 
 // Re-export all segment types
-pub use context_window::ContextWindowSegment;
+pub use burn_rate::BurnRateSegment;
+pub use cache_efficiency::CacheEfficiencySegment;
+pub use context_window::{ContextWindowSegment, SparklineConfig};
 pub use cost::CostSegment;
 pub use directory::DirectorySegment;
 pub use git::GitSegment;
 pub use model::ModelSegment;
 pub use output_style::OutputStyleSegment;
+pub use plan::PlanSegment;
 pub use session::SessionSegment;
+pub use spend::SpendSegment;
 pub use update::UpdateSegment;
 pub use usage::UsageSegment;
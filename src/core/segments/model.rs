@@ -13,12 +13,27 @@ impl ModelSegment {
 
 impl Segment for ModelSegment {
     fn collect(&self, input: &InputData) -> Option<SegmentData> {
+        let model_config = ModelConfig::load();
         let mut metadata = HashMap::new();
         metadata.insert("model_id".to_string(), input.model.id.clone());
         metadata.insert("display_name".to_string(), input.model.display_name.clone());
 
+        if let Some(entry) = model_config.find(&input.model.id) {
+            metadata.insert("context_limit".to_string(), entry.context_limit.to_string());
+            if let Some(max_output) = entry.max_output_tokens {
+                metadata.insert("max_output_tokens".to_string(), max_output.to_string());
+            }
+            if let Some(icon) = &entry.icon {
+                metadata.insert("dynamic_icon".to_string(), icon.clone());
+            }
+        }
+
         Some(SegmentData {
-            primary: self.format_model_name(&input.model.id, &input.model.display_name),
+            primary: self.format_model_name(
+                model_config,
+                &input.model.id,
+                &input.model.display_name,
+            ),
             secondary: String::new(),
             metadata,
         })
@@ -30,9 +45,12 @@ impl Segment for ModelSegment {
 }
 
 impl ModelSegment {
-    fn format_model_name(&self, id: &str, display_name: &str) -> String {
-        let model_config = ModelConfig::load();
-
+    fn format_model_name(
+        &self,
+        model_config: &ModelConfig,
+        id: &str,
+        display_name: &str,
+    ) -> String {
         // Try to get display name from external config first
         if let Some(config_name) = model_config.get_display_name(id) {
             config_name
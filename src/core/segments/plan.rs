@@ -0,0 +1,239 @@
+use super::{Segment, SegmentData};
+use crate::config::{CredentialsConfig, InputData, SegmentId};
+use crate::utils::claude_config::{self, read_json, Settings};
+use crate::utils::credentials;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// How the session is billed, in Claude Code's order of precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Billing {
+    Bedrock,
+    Vertex,
+    /// `ANTHROPIC_AUTH_TOKEN`, `ANTHROPIC_API_KEY` or `apiKeyHelper`
+    ApiKey(&'static str),
+    OAuth,
+}
+
+impl Billing {
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Bedrock => "bedrock",
+            Self::Vertex => "vertex",
+            Self::ApiKey(_) => "api_key",
+            Self::OAuth => "oauth",
+        }
+    }
+}
+
+pub struct PlanSegment {
+    credentials: CredentialsConfig,
+    show_account: bool,
+}
+
+impl Default for PlanSegment {
+    fn default() -> Self {
+        Self {
+            credentials: CredentialsConfig::default(),
+            show_account: true,
+        }
+    }
+}
+
+impl PlanSegment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_credentials(mut self, credentials: CredentialsConfig) -> Self {
+        self.credentials = credentials;
+        self
+    }
+
+    pub fn with_account(mut self, show_account: bool) -> Self {
+        self.show_account = show_account;
+        self
+    }
+
+    fn detect_billing(settings: &Settings) -> Option<Billing> {
+        if settings.flag("CLAUDE_CODE_USE_BEDROCK") {
+            return Some(Billing::Bedrock);
+        }
+        if settings.flag("CLAUDE_CODE_USE_VERTEX") {
+            return Some(Billing::Vertex);
+        }
+        for name in ["ANTHROPIC_AUTH_TOKEN", "ANTHROPIC_API_KEY"] {
+            if settings.var(name).is_some() {
+                return Some(Billing::ApiKey(name));
+            }
+        }
+        if settings.api_key_helper {
+            return Some(Billing::ApiKey("apiKeyHelper"));
+        }
+        None
+    }
+
+    /// "max" -> "Max", "claude_enterprise" -> "Enterprise"
+    fn plan_label(subscription_type: &str) -> String {
+        let name = subscription_type
+            .trim()
+            .trim_start_matches("claude_")
+            .replace('_', " ");
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+
+    /// Signed-in account from Claude Code's global config, as (email, organization)
+    fn oauth_account() -> Option<(Option<String>, Option<String>)> {
+        let config = read_json(&claude_config::global_config_file()?)?;
+        let account = config.get("oauthAccount")?;
+        let field = |name: &str| {
+            account
+                .get(name)
+                .and_then(|value| value.as_str())
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+        };
+        Some((field("emailAddress"), field("organizationName")))
+    }
+}
+
+impl Segment for PlanSegment {
+    fn collect(&self, input: &InputData) -> Option<SegmentData> {
+        let project_dir = match &input.workspace.project_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => claude_config::project_root(Path::new(&input.workspace.current_dir)),
+        };
+        let settings = Settings::load(Some(&project_dir));
+        let mut metadata = HashMap::new();
+        let mut secondary_parts = Vec::new();
+
+        let (billing, token) = match Self::detect_billing(&settings) {
+            Some(billing) => (billing, None),
+            // credential_command is too slow for the render path
+            None => (
+                Billing::OAuth,
+                Some(credentials::load_token(&self.credentials, false)?),
+            ),
+        };
+        metadata.insert("billing".to_string(), billing.key().to_string());
+
+        let primary = match billing {
+            Billing::Bedrock => "Bedrock".to_string(),
+            Billing::Vertex => "Vertex".to_string(),
+            Billing::ApiKey(source) => {
+                metadata.insert("auth_source".to_string(), source.to_string());
+                secondary_parts.push(source.to_string());
+                "API key".to_string()
+            }
+            Billing::OAuth => {
+                let token = token?;
+                if !token.scopes.is_empty() {
+                    metadata.insert("scopes".to_string(), token.scopes.join(","));
+                }
+                if let Some((email, organization)) = Self::oauth_account() {
+                    if let Some(organization) = organization {
+                        metadata.insert("organization".to_string(), organization);
+                    }
+                    if let Some(email) = email {
+                        metadata.insert("account_email".to_string(), email.clone());
+                        if self.show_account {
+                            secondary_parts.push(email);
+                        }
+                    }
+                }
+
+                match token.subscription_type.as_deref().map(Self::plan_label) {
+                    Some(plan) if !plan.is_empty() => {
+                        metadata.insert("plan".to_string(), plan.clone());
+                        secondary_parts.insert(0, "OAuth".to_string());
+                        plan
+                    }
+                    _ => "OAuth".to_string(),
+                }
+            }
+        };
+
+        let secondary = if secondary_parts.is_empty() {
+            String::new()
+        } else {
+            format!("· {}", secondary_parts.join(" · "))
+        };
+
+        Some(SegmentData {
+            primary,
+            secondary,
+            metadata,
+        })
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::Plan
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(env: &[(&str, &str)], api_key_helper: bool) -> Settings {
+        Settings {
+            env: env
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            api_key_helper,
+        }
+    }
+
+    #[test]
+    fn billing_follows_claude_code_precedence() {
+        let mut env = vec![
+            ("CLAUDE_CODE_USE_BEDROCK", "1"),
+            ("CLAUDE_CODE_USE_VERTEX", "1"),
+            ("ANTHROPIC_AUTH_TOKEN", "token"),
+            ("ANTHROPIC_API_KEY", "sk-ant"),
+        ];
+        let detect =
+            |env: &[(&str, &str)], helper| PlanSegment::detect_billing(&settings(env, helper));
+
+        assert_eq!(detect(&env, true), Some(Billing::Bedrock));
+        env.remove(0);
+        assert_eq!(detect(&env, true), Some(Billing::Vertex));
+        env.remove(0);
+        assert_eq!(
+            detect(&env, true),
+            Some(Billing::ApiKey("ANTHROPIC_AUTH_TOKEN"))
+        );
+        env.remove(0);
+        assert_eq!(
+            detect(&env, true),
+            Some(Billing::ApiKey("ANTHROPIC_API_KEY"))
+        );
+        env.remove(0);
+        assert_eq!(detect(&env, true), Some(Billing::ApiKey("apiKeyHelper")));
+        // Left to the OAuth credentials
+        assert_eq!(detect(&env, false), None);
+    }
+
+    #[test]
+    fn disabled_flags_and_empty_keys_are_ignored() {
+        let env = [
+            ("CLAUDE_CODE_USE_BEDROCK", "0"),
+            ("CLAUDE_CODE_USE_VERTEX", "false"),
+            ("ANTHROPIC_API_KEY", " "),
+        ];
+        assert_eq!(PlanSegment::detect_billing(&settings(&env, false)), None);
+    }
+
+    #[test]
+    fn plan_labels_drop_the_claude_prefix() {
+        assert_eq!(PlanSegment::plan_label("max"), "Max");
+        assert_eq!(PlanSegment::plan_label("claude_enterprise"), "Enterprise");
+        assert_eq!(PlanSegment::plan_label("claude_team_plus"), "Team plus");
+        assert_eq!(PlanSegment::plan_label(""), "");
+    }
+}
@@ -0,0 +1,166 @@
+use super::{Segment, SegmentData};
+use crate::config::{InputData, ModelConfig, SegmentId};
+use crate::core::spend::{format_tokens, SpendIndex, SpendPeriod, SpendRow};
+use crate::utils::process;
+use crate::utils::state_file::StateFile;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// A refresh lock older than this is left over from a crashed rescan
+const REFRESH_LOCK_STALE_SECS: u64 = 600;
+
+/// Totals for the current day, week and month, refreshed periodically
+#[derive(Debug, Serialize, Deserialize)]
+struct SpendSummary {
+    computed_at: DateTime<Utc>,
+    day: NaiveDate,
+    today: SpendRow,
+    week: SpendRow,
+    month: SpendRow,
+}
+
+impl SpendSummary {
+    fn file() -> Option<StateFile> {
+        StateFile::in_state_dir(".spend_summary.json")
+    }
+
+    fn lock_path() -> Option<PathBuf> {
+        Some(Self::file()?.path().with_extension("lock"))
+    }
+
+    fn load() -> Option<Self> {
+        Self::file()?.load()
+    }
+
+    fn save(&self) {
+        if let Some(file) = Self::file() {
+            let _ = file.save(self);
+        }
+    }
+
+    fn compute(today: NaiveDate) -> Self {
+        let models = ModelConfig::load();
+        let ranges = [SpendPeriod::Today, SpendPeriod::Week, SpendPeriod::Month]
+            .map(|period| period.range(today));
+
+        // Files untouched since the earliest period began cannot contribute
+        let earliest = ranges.iter().map(|(from, _)| *from).min().unwrap_or(today);
+        let since = earliest
+            .and_hms_opt(0, 0, 0)
+            .and_then(|t| t.and_local_timezone(Local).earliest())
+            .map(SystemTime::from);
+        let index = SpendIndex::update(since);
+
+        let [today_range, week_range, month_range] =
+            ranges.map(|(from, to)| index.report(from, to, models).total);
+        Self {
+            computed_at: Utc::now(),
+            day: today,
+            today: today_range,
+            week: week_range,
+            month: month_range,
+        }
+    }
+
+    fn row(&self, period: SpendPeriod) -> &SpendRow {
+        match period {
+            SpendPeriod::Today => &self.today,
+            SpendPeriod::Week => &self.week,
+            SpendPeriod::Month => &self.month,
+        }
+    }
+}
+
+pub struct SpendSegment {
+    period: SpendPeriod,
+    refresh_interval: u64,
+}
+
+impl Default for SpendSegment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpendSegment {
+    pub fn new() -> Self {
+        Self {
+            period: SpendPeriod::Today,
+            refresh_interval: 60,
+        }
+    }
+
+    pub fn with_period(mut self, period: SpendPeriod) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// Seconds between rescans of the project transcripts
+    pub fn with_refresh_interval(mut self, seconds: u64) -> Self {
+        self.refresh_interval = seconds;
+        self
+    }
+
+    /// The last computed summary, starting a background refresh once it is
+    /// older than the refresh interval
+    ///
+    /// A summary left over from an earlier day is not shown while the
+    /// refresh runs, since its today and week totals no longer apply.
+    fn summary(&self) -> Option<SpendSummary> {
+        let today = Local::now().date_naive();
+        let summary = SpendSummary::load();
+        let fresh = summary.as_ref().is_some_and(|summary| {
+            let age = Utc::now().signed_duration_since(summary.computed_at);
+            summary.day == today
+                && age.num_seconds() >= 0
+                && (age.num_seconds() as u64) < self.refresh_interval
+        });
+        if !fresh {
+            if let Some(lock_path) = SpendSummary::lock_path() {
+                process::spawn_refresh(&lock_path, REFRESH_LOCK_STALE_SECS, "refresh-spend");
+            }
+        }
+        summary.filter(|summary| summary.day == today)
+    }
+
+    /// Rescan the transcripts and save a new summary; run by the background
+    /// refresh process, which owns the refresh lock and releases it when done
+    pub fn refresh_summary() {
+        SpendSummary::compute(Local::now().date_naive()).save();
+
+        if let Some(lock_path) = SpendSummary::lock_path() {
+            let _ = std::fs::remove_file(lock_path);
+        }
+    }
+}
+
+impl Segment for SpendSegment {
+    fn collect(&self, _input: &InputData) -> Option<SegmentData> {
+        let summary = self.summary()?;
+        let row = summary.row(self.period);
+
+        let mut metadata = HashMap::new();
+        for period in [SpendPeriod::Today, SpendPeriod::Week, SpendPeriod::Month] {
+            let row = summary.row(period);
+            metadata.insert(format!("{}_cost", period.label()), row.cost.to_string());
+            metadata.insert(
+                format!("{}_tokens", period.label()),
+                row.totals.tokens().to_string(),
+            );
+        }
+        metadata.insert("period".to_string(), self.period.label().to_string());
+
+        Some(SegmentData {
+            primary: format!("{} ${:.2}", self.period.label(), row.cost),
+            secondary: format!("· {} tok", format_tokens(row.totals.tokens())),
+            metadata,
+        })
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::Spend
+    }
+}
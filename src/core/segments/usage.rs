@@ -1,36 +1,356 @@
 use super::{Segment, SegmentData};
-use crate::config::{InputData, SegmentId};
-use crate::utils::credentials;
-use chrono::{DateTime, Datelike, Duration, Local, Timelike, Utc};
+use crate::config::{CredentialsConfig, InputData, NetworkConfig, SegmentId};
+use crate::core::transcript::fnv1a;
+use crate::utils::http::NetworkPolicy;
+use crate::utils::state_file::StateFile;
+use crate::utils::{claude_code_version, credentials, process};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Duration, Local, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+/// One bucket of the usage response, e.g. `five_hour`, `seven_day_opus` or
+/// `extra_usage`; every field is optional since buckets differ in shape
+#[derive(Debug, Clone, Default, Deserialize)]
+struct UsageBucket {
+    #[serde(default)]
+    utilization: Option<f64>,
+    #[serde(default)]
+    resets_at: Option<String>,
+    /// Bucket-specific fields such as extra-usage credits
+    #[serde(flatten)]
+    extra: BTreeMap<String, serde_json::Value>,
+}
 
-#[derive(Debug, Deserialize)]
-struct ApiUsageResponse {
-    five_hour: UsagePeriod,
-    seven_day: UsagePeriod,
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ApiUsageCache {
+    /// Every non-null bucket as the endpoint returned it, including ones this
+    /// version does not know about
+    #[serde(default)]
+    buckets: BTreeMap<String, serde_json::Value>,
+    /// Time of the last successful fetch
+    #[serde(default)]
+    cached_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<UsageError>,
 }
 
-#[derive(Debug, Deserialize)]
-struct UsagePeriod {
-    utilization: f64,
-    resets_at: Option<String>,
+/// Why the last refresh failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum UsageErrorKind {
+    /// 401/403: the OAuth token was rejected
+    Auth,
+    /// The token's recorded expiry has passed; no request was made
+    Expired,
+    RateLimited,
+    Server,
+    /// Any other status, or a body that is not a usage response
+    Api,
+    /// Connection failure or timeout
+    Network,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ApiUsageCache {
+impl UsageErrorKind {
+    fn from_status(status: u16) -> Self {
+        match status {
+            401 | 403 => Self::Auth,
+            429 => Self::RateLimited,
+            500..=599 => Self::Server,
+            _ => Self::Api,
+        }
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::Expired => "expired",
+            Self::RateLimited => "rate_limited",
+            Self::Server => "server",
+            Self::Api => "api",
+            Self::Network => "network",
+        }
+    }
+
+    /// Shown in place of the usage when there is no cached data at all
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Auth => "auth expired",
+            Self::Expired => "token expired",
+            Self::RateLimited => "rate limited",
+            Self::Server => "server error",
+            Self::Api => "api error",
+            Self::Network => "offline",
+        }
+    }
+
+    /// Appended to cached figures the error has left out of date
+    fn marker(&self) -> &'static str {
+        match self {
+            Self::Auth | Self::Expired => self.label(),
+            _ => "stale",
+        }
+    }
+}
+
+/// Last failed refresh, persisted so every session honours the same backoff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UsageError {
+    kind: UsageErrorKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    /// Consecutive failures, which set the backoff
+    failures: u32,
+    retry_at: DateTime<Utc>,
+    /// Hash of the token an auth or expiry error was for; a new token is tried
+    /// right away
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token_hash: Option<u64>,
+}
+
+impl UsageError {
+    /// Whether the error still holds for `token`; an unknown token (one only
+    /// `credential_command` can supply) is assumed unchanged
+    fn applies_to(&self, token: Option<&str>) -> bool {
+        match (self.token_hash, token) {
+            (Some(hash), Some(token)) => hash == fnv1a(token.as_bytes()),
+            _ => true,
+        }
+    }
+}
+
+/// A failed usage request
+struct FetchError {
+    kind: UsageErrorKind,
+    status: Option<u16>,
+    /// Seconds from a 429's `Retry-After` header
+    retry_after: Option<i64>,
+}
+
+/// Delay before retrying after `failures` consecutive failures
+fn backoff_secs(failures: u32) -> i64 {
+    let doublings = failures.saturating_sub(1).min(16);
+    (BACKOFF_BASE_SECS << doublings).min(BACKOFF_MAX_SECS)
+}
+
+/// Cache layout from before all buckets were kept
+#[derive(Debug, Deserialize)]
+struct LegacyApiUsageCache {
     five_hour_utilization: f64,
     seven_day_utilization: f64,
-    resets_at: Option<String>,
+    #[serde(default)]
+    five_hour_resets_at: Option<String>,
+    #[serde(alias = "resets_at")]
+    seven_day_resets_at: Option<String>,
     cached_at: String,
 }
 
-#[derive(Default)]
-pub struct UsageSegment;
+/// Either cache layout; every field of the current one is optional, so the
+/// legacy layout has to be tried first
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredUsageCache {
+    Legacy(LegacyApiUsageCache),
+    Current(ApiUsageCache),
+}
+
+impl From<LegacyApiUsageCache> for ApiUsageCache {
+    fn from(legacy: LegacyApiUsageCache) -> Self {
+        let bucket = |utilization: f64, resets_at: Option<String>| serde_json::json!({ "utilization": utilization, "resets_at": resets_at });
+        let mut buckets = BTreeMap::new();
+        buckets.insert(
+            "five_hour".to_string(),
+            bucket(legacy.five_hour_utilization, legacy.five_hour_resets_at),
+        );
+        buckets.insert(
+            "seven_day".to_string(),
+            bucket(legacy.seven_day_utilization, legacy.seven_day_resets_at),
+        );
+        Self {
+            buckets,
+            cached_at: Some(legacy.cached_at),
+            error: None,
+        }
+    }
+}
+
+impl ApiUsageCache {
+    fn bucket(&self, name: &str) -> Option<UsageBucket> {
+        serde_json::from_value(self.buckets.get(name)?.clone()).ok()
+    }
+
+    /// Whether the last failure's backoff still forbids a refresh with `token`
+    fn backing_off(&self, token: Option<&str>, now: DateTime<Utc>) -> bool {
+        self.error
+            .as_ref()
+            .is_some_and(|error| now < error.retry_at && error.applies_to(token))
+    }
+}
+
+/// Canonical bucket name for a `windows` option entry
+fn bucket_name(name: &str) -> &str {
+    match name {
+        "5h" => "five_hour",
+        "7d" => "seven_day",
+        other => other,
+    }
+}
+
+/// Short label shown before a bucket's percentage
+fn bucket_label(name: &str) -> String {
+    match name {
+        "five_hour" => "5h".to_string(),
+        "seven_day" => "7d".to_string(),
+        "extra_usage" => "extra".to_string(),
+        other => match other.strip_prefix("seven_day_") {
+            Some(scope) => format!("7d {}", scope.replace('_', " ")),
+            None => other.replace('_', " "),
+        },
+    }
+}
+
+/// How a window's reset time is shown
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ResetFormat {
+    /// Countdown such as "resets in 2h13m"
+    #[default]
+    Relative,
+    /// Local time through a strftime pattern, e.g. "%a %H:%M"
+    Strftime(String),
+    Hidden,
+}
+
+impl ResetFormat {
+    pub fn from_option(value: &str) -> Self {
+        match value {
+            "relative" => Self::Relative,
+            "none" => Self::Hidden,
+            pattern => {
+                // An invalid pattern would make chrono's Display panic
+                if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+                    Self::Relative
+                } else {
+                    Self::Strftime(pattern.to_string())
+                }
+            }
+        }
+    }
+
+    fn format(&self, resets_at: Option<&str>, now: DateTime<Utc>) -> Option<String> {
+        let resets_at = DateTime::parse_from_rfc3339(resets_at?).ok()?;
+        match self {
+            Self::Hidden => None,
+            Self::Strftime(pattern) => Some(format!(
+                "resets {}",
+                resets_at.with_timezone(&Local).format(pattern)
+            )),
+            Self::Relative => {
+                let remaining = resets_at.with_timezone(&Utc) - now;
+                if remaining.num_minutes() < 1 {
+                    return Some("resets now".to_string());
+                }
+                Some(format!("resets in {}", format_countdown(remaining)))
+            }
+        }
+    }
+}
+
+/// Compact countdown with the two most significant units: "13m", "2h13m", "3d4h"
+fn format_countdown(remaining: Duration) -> String {
+    let minutes = remaining.num_minutes();
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d{}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h{}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+/// Added to the request timeout before a refresh lock counts as abandoned
+const REFRESH_LOCK_GRACE_SECS: u64 = 30;
+
+/// Retry delay after the first failed refresh; doubles with each further failure
+const BACKOFF_BASE_SECS: i64 = 30;
+
+const BACKOFF_MAX_SECS: i64 = 3600;
+
+/// Usage segment options from the config file
+struct UsageOptions {
+    api_base_url: String,
+    cache_duration: u64,
+    timeout: u64,
+    credentials: CredentialsConfig,
+    network: NetworkConfig,
+}
+
+impl UsageOptions {
+    fn load() -> Self {
+        let config = crate::config::Config::load().ok();
+        let options = config
+            .as_ref()
+            .and_then(|c| c.segments.iter().find(|s| s.id == SegmentId::Usage))
+            .map(|sc| &sc.options);
+        let network = config
+            .as_ref()
+            .and_then(|c| c.network.clone())
+            .unwrap_or_default();
+
+        Self {
+            api_base_url: options
+                .and_then(|o| o.get("api_base_url"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("https://api.anthropic.com")
+                .to_string(),
+            cache_duration: options
+                .and_then(|o| o.get("cache_duration"))
+                .and_then(|v| v.as_u64())
+                .unwrap_or(300),
+            timeout: options
+                .and_then(|o| o.get("timeout"))
+                .and_then(|v| v.as_u64())
+                .or(network.timeout)
+                .unwrap_or(2),
+            credentials: config.and_then(|c| c.credentials).unwrap_or_default(),
+            network,
+        }
+    }
+}
+
+pub struct UsageSegment {
+    windows: Vec<String>,
+    reset_format: ResetFormat,
+}
+
+impl Default for UsageSegment {
+    fn default() -> Self {
+        Self {
+            windows: vec!["five_hour".to_string(), "seven_day".to_string()],
+            reset_format: ResetFormat::default(),
+        }
+    }
+}
 
 impl UsageSegment {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Buckets to show, in order; the first one present is the primary text
+    pub fn with_windows(mut self, windows: Vec<String>) -> Self {
+        if !windows.is_empty() {
+            self.windows = windows
+                .iter()
+                .map(|name| bucket_name(name).to_string())
+                .collect();
+        }
+        self
+    }
+
+    pub fn with_reset_format(mut self, reset_format: ResetFormat) -> Self {
+        self.reset_format = reset_format;
+        self
     }
 
     fn get_circle_icon(utilization: f64) -> String {
@@ -47,24 +367,6 @@ impl UsageSegment {
         }
     }
 
-    fn format_reset_time(reset_time_str: Option<&str>) -> String {
-        if let Some(time_str) = reset_time_str {
-            if let Ok(dt) = DateTime::parse_from_rfc3339(time_str) {
-                let mut local_dt = dt.with_timezone(&Local);
-                if local_dt.minute() > 45 {
-                    local_dt += Duration::hours(1);
-                }
-                return format!(
-                    "{}-{}-{}",
-                    local_dt.month(),
-                    local_dt.day(),
-                    local_dt.hour()
-                );
-            }
-        }
-        "?".to_string()
-    }
-
     fn get_cache_path() -> Option<std::path::PathBuf> {
         let home = dirs::home_dir()?;
         Some(
@@ -75,192 +377,312 @@ impl UsageSegment {
     }
 
     fn load_cache(&self) -> Option<ApiUsageCache> {
-        let cache_path = Self::get_cache_path()?;
-        if !cache_path.exists() {
-            return None;
-        }
+        let stored = StateFile::new(Self::get_cache_path()?).load::<StoredUsageCache>()?;
+        Some(match stored {
+            StoredUsageCache::Legacy(legacy) => legacy.into(),
+            StoredUsageCache::Current(cache) => cache,
+        })
+    }
 
-        let content = std::fs::read_to_string(&cache_path).ok()?;
-        serde_json::from_str(&content).ok()
+    /// Last known 5-hour utilization from the cache, without any network request
+    pub fn cached_five_hour_utilization() -> Option<f64> {
+        Self::new().load_cache()?.bucket("five_hour")?.utilization
     }
 
     fn save_cache(&self, cache: &ApiUsageCache) {
         if let Some(cache_path) = Self::get_cache_path() {
-            if let Some(parent) = cache_path.parent() {
-                let _ = std::fs::create_dir_all(parent);
-            }
-            if let Ok(json) = serde_json::to_string_pretty(cache) {
-                let _ = std::fs::write(&cache_path, json);
-            }
+            let _ = StateFile::new(cache_path).save(cache);
         }
     }
 
-    fn is_cache_valid(&self, cache: &ApiUsageCache, cache_duration: u64) -> bool {
-        if let Ok(cached_at) = DateTime::parse_from_rfc3339(&cache.cached_at) {
-            let now = Utc::now();
-            let elapsed = now.signed_duration_since(cached_at.with_timezone(&Utc));
-            elapsed.num_seconds() < cache_duration as i64
-        } else {
-            false
-        }
+    fn get_lock_path() -> Option<std::path::PathBuf> {
+        Some(Self::get_cache_path()?.with_extension("lock"))
     }
 
-    fn get_claude_code_version() -> String {
-        use std::process::Command;
-
-        let output = Command::new("npm")
-            .args(["view", "@anthropic-ai/claude-code", "version"])
-            .output();
+    /// Refresh the cache in a detached `ccline refresh-usage` process so the
+    /// render never waits on the network
+    fn spawn_refresh(options: &UsageOptions) {
+        let Some(lock_path) = Self::get_lock_path() else {
+            return;
+        };
+        let stale_after = options.timeout.saturating_add(REFRESH_LOCK_GRACE_SECS);
+        process::spawn_refresh(&lock_path, stale_after, "refresh-usage");
+    }
 
-        match output {
-            Ok(output) if output.status.success() => {
-                let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                if !version.is_empty() {
-                    return format!("claude-code/{}", version);
-                }
-            }
-            _ => {}
+    /// Nothing cached to show; surface why instead of disappearing
+    fn error_only(error: UsageErrorKind) -> SegmentData {
+        let mut metadata = HashMap::new();
+        metadata.insert("usage_error".to_string(), error.key().to_string());
+        SegmentData {
+            primary: error.label().to_string(),
+            secondary: String::new(),
+            metadata,
         }
+    }
+
+    /// Fetch usage and update the cache; run by the background refresh process,
+    /// which owns the refresh lock and releases it when done
+    pub fn refresh_cache() {
+        Self::new().refresh();
 
-        "claude-code".to_string()
+        if let Some(lock_path) = Self::get_lock_path() {
+            let _ = std::fs::remove_file(lock_path);
+        }
     }
 
-    fn get_proxy_from_settings() -> Option<String> {
-        let home = std::env::var("HOME")
-            .or_else(|_| std::env::var("USERPROFILE"))
-            .ok()?;
-        let settings_path = format!("{}/.claude/settings.json", home);
+    fn refresh(&self) {
+        let options = UsageOptions::load();
+        if options.network.offline {
+            return;
+        }
+        let Some(oauth) = credentials::load_token(&options.credentials, true) else {
+            return;
+        };
+        let token = oauth.access_token.as_str();
+        let mut cache = self.load_cache().unwrap_or_default();
+        let now = Utc::now();
+
+        // Checked again under the lock: another session may have refreshed, or
+        // failed and started a backoff, since this one was spawned
+        if cache.backing_off(Some(token), now)
+            || self.is_cache_valid(&cache, options.cache_duration)
+        {
+            return;
+        }
 
-        let content = std::fs::read_to_string(&settings_path).ok()?;
-        let settings: serde_json::Value = serde_json::from_str(&content).ok()?;
+        let result = if oauth.is_expired() {
+            Err(FetchError {
+                kind: UsageErrorKind::Expired,
+                status: None,
+                retry_after: None,
+            })
+        } else {
+            let network = NetworkPolicy::new(options.network.clone());
+            self.fetch_api_usage(&network, &options.api_base_url, token, options.timeout)
+        };
+        match result {
+            Ok(buckets) => {
+                cache.buckets = buckets;
+                cache.cached_at = Some(now.to_rfc3339());
+                cache.error = None;
+            }
+            Err(err) => {
+                let failures = cache.error.as_ref().map_or(0, |e| e.failures) + 1;
+                let delay = backoff_secs(failures).max(err.retry_after.unwrap_or(0));
+                cache.error = Some(UsageError {
+                    kind: err.kind,
+                    status: err.status,
+                    failures,
+                    retry_at: now + Duration::seconds(delay),
+                    token_hash: matches!(err.kind, UsageErrorKind::Auth | UsageErrorKind::Expired)
+                        .then(|| fnv1a(token.as_bytes())),
+                });
+            }
+        }
+        self.save_cache(&cache);
+    }
 
-        // Try HTTPS_PROXY first, then HTTP_PROXY
-        settings
-            .get("env")?
-            .get("HTTPS_PROXY")
-            .or_else(|| settings.get("env")?.get("HTTP_PROXY"))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
+    fn is_cache_valid(&self, cache: &ApiUsageCache, cache_duration: u64) -> bool {
+        if let Some(cached_at) = cache
+            .cached_at
+            .as_deref()
+            .and_then(|cached_at| DateTime::parse_from_rfc3339(cached_at).ok())
+        {
+            let now = Utc::now();
+            let elapsed = now.signed_duration_since(cached_at.with_timezone(&Utc));
+            elapsed.num_seconds() < cache_duration as i64
+        } else {
+            false
+        }
     }
 
     fn fetch_api_usage(
         &self,
+        network: &NetworkPolicy,
         api_base_url: &str,
         token: &str,
         timeout_secs: u64,
-    ) -> Option<ApiUsageResponse> {
+    ) -> Result<BTreeMap<String, serde_json::Value>, FetchError> {
         let url = format!("{}/api/oauth/usage", api_base_url);
-        let user_agent = Self::get_claude_code_version();
-
-        let mut agent_builder = ureq::AgentBuilder::new();
-
-        // Configure proxy from Claude settings if available
-        if let Some(proxy_url) = Self::get_proxy_from_settings() {
-            if let Ok(proxy) = ureq::Proxy::new(&proxy_url) {
-                agent_builder = agent_builder.proxy(proxy);
-            }
-        }
+        let user_agent = claude_code_version::user_agent();
 
-        let agent = agent_builder.build();
+        let agent = network
+            .agent(&url, std::time::Duration::from_secs(timeout_secs))
+            .map_err(|_| FetchError {
+                kind: UsageErrorKind::Network,
+                status: None,
+                retry_after: None,
+            })?;
 
         let response = agent
             .get(&url)
             .set("Authorization", &format!("Bearer {}", token))
             .set("anthropic-beta", "oauth-2025-04-20")
             .set("User-Agent", &user_agent)
-            .timeout(std::time::Duration::from_secs(timeout_secs))
-            .call()
-            .ok()?;
+            .call();
+
+        let response = match response {
+            Ok(response) if response.status() == 200 => response,
+            Ok(response) => {
+                return Err(FetchError {
+                    kind: UsageErrorKind::from_status(response.status()),
+                    status: Some(response.status()),
+                    retry_after: None,
+                })
+            }
+            Err(ureq::Error::Status(status, response)) => {
+                return Err(FetchError {
+                    kind: UsageErrorKind::from_status(status),
+                    status: Some(status),
+                    retry_after: response
+                        .header("Retry-After")
+                        .and_then(|value| value.trim().parse().ok()),
+                })
+            }
+            Err(ureq::Error::Transport(_)) => {
+                return Err(FetchError {
+                    kind: UsageErrorKind::Network,
+                    status: None,
+                    retry_after: None,
+                })
+            }
+        };
 
-        if response.status() == 200 {
-            response.into_json().ok()
-        } else {
-            None
-        }
+        // Null buckets (e.g. limits that don't apply to the plan) are dropped
+        let buckets: BTreeMap<String, serde_json::Value> =
+            response.into_json().map_err(|_| FetchError {
+                kind: UsageErrorKind::Api,
+                status: Some(200),
+                retry_after: None,
+            })?;
+        Ok(buckets
+            .into_iter()
+            .filter(|(_, bucket)| !bucket.is_null())
+            .collect())
     }
 }
 
 impl Segment for UsageSegment {
-    fn collect(&self, _input: &InputData) -> Option<SegmentData> {
-        let token = credentials::get_oauth_token()?;
-
-        // Load config from file to get segment options
-        let config = crate::config::Config::load().ok()?;
-        let segment_config = config.segments.iter().find(|s| s.id == SegmentId::Usage);
-
-        let api_base_url = segment_config
-            .and_then(|sc| sc.options.get("api_base_url"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("https://api.anthropic.com");
-
-        let cache_duration = segment_config
-            .and_then(|sc| sc.options.get("cache_duration"))
-            .and_then(|v| v.as_u64())
-            .unwrap_or(300);
+    fn collect(&self, input: &InputData) -> Option<SegmentData> {
+        let options = UsageOptions::load();
+        // credential_command is left to the background refresh
+        let oauth = credentials::load_token(&options.credentials, false);
+        if oauth.is_none() && !credentials::has_command(&options.credentials) {
+            return None;
+        }
+        let token = oauth.as_ref().map(|oauth| oauth.access_token.as_str());
+        let token_expired = oauth.as_ref().is_some_and(|oauth| oauth.is_expired());
 
-        let timeout = segment_config
-            .and_then(|sc| sc.options.get("timeout"))
-            .and_then(|v| v.as_u64())
-            .unwrap_or(2);
+        // Picked up by the background refresh for its User-Agent
+        if let Some(version) = &input.version {
+            claude_code_version::remember(version);
+        }
+        let now = Utc::now();
 
+        // Always render from the cache; a stale or missing cache is refreshed
+        // in the background and picked up by a later render
         let cached_data = self.load_cache();
-        let use_cached = cached_data
+        let fresh = cached_data
             .as_ref()
-            .map(|cache| self.is_cache_valid(cache, cache_duration))
+            .map(|cache| self.is_cache_valid(cache, options.cache_duration))
             .unwrap_or(false);
+        let backing_off = cached_data
+            .as_ref()
+            .is_some_and(|cache| cache.backing_off(token, now));
+        let offline = options.network.offline;
+        if !fresh && !backing_off && !token_expired && !offline {
+            Self::spawn_refresh(&options);
+        }
 
-        let (five_hour_util, seven_day_util, resets_at) = if use_cached {
-            let cache = cached_data.unwrap();
-            (
-                cache.five_hour_utilization,
-                cache.seven_day_utilization,
-                cache.resets_at,
-            )
+        // An expired token is reported straight away, before any refresh fails,
+        // and out-of-date figures are marked while offline
+        let error = cached_data
+            .as_ref()
+            .and_then(|cache| cache.error.as_ref())
+            .filter(|error| error.applies_to(token))
+            .map(|error| error.kind);
+        let error = if token_expired {
+            Some(UsageErrorKind::Expired)
+        } else if offline && !fresh {
+            Some(UsageErrorKind::Network)
         } else {
-            match self.fetch_api_usage(api_base_url, &token, timeout) {
-                Some(response) => {
-                    let cache = ApiUsageCache {
-                        five_hour_utilization: response.five_hour.utilization,
-                        seven_day_utilization: response.seven_day.utilization,
-                        resets_at: response.seven_day.resets_at.clone(),
-                        cached_at: Utc::now().to_rfc3339(),
-                    };
-                    self.save_cache(&cache);
-                    (
-                        response.five_hour.utilization,
-                        response.seven_day.utilization,
-                        response.seven_day.resets_at,
-                    )
-                }
-                None => {
-                    if let Some(cache) = cached_data {
-                        (
-                            cache.five_hour_utilization,
-                            cache.seven_day_utilization,
-                            cache.resets_at,
-                        )
-                    } else {
-                        return None;
-                    }
-                }
-            }
+            error
+        };
+        let Some(cache) = cached_data else {
+            return error.map(Self::error_only);
         };
 
-        let dynamic_icon = Self::get_circle_icon(seven_day_util / 100.0);
-        let five_hour_percent = five_hour_util.round() as u8;
-        let primary = format!("{}%", five_hour_percent);
-        let secondary = format!("· {}", Self::format_reset_time(resets_at.as_deref()));
+        // Buckets missing from the response (or without a utilization) are skipped
+        let shown: Vec<(&str, f64, Option<String>)> = self
+            .windows
+            .iter()
+            .filter_map(|name| {
+                let bucket = cache.bucket(name)?;
+                Some((name.as_str(), bucket.utilization?, bucket.resets_at))
+            })
+            .collect();
+        let Some(&(_, primary_utilization, _)) = shown.first() else {
+            return error.map(Self::error_only);
+        };
+
+        // A single bucket keeps the plain "42%" look; several are labelled
+        let labelled = shown.len() > 1;
+        let mut primary = String::new();
+        let mut secondary_parts = Vec::new();
+        for (index, (name, utilization, resets_at)) in shown.iter().enumerate() {
+            let percent = if labelled {
+                format!("{} {}%", bucket_label(name), utilization.round() as u8)
+            } else {
+                format!("{}%", utilization.round() as u8)
+            };
+            if index == 0 {
+                primary = percent;
+            } else {
+                secondary_parts.push(percent);
+            }
+            if let Some(reset) = self.reset_format.format(resets_at.as_deref(), now) {
+                secondary_parts.push(reset);
+            }
+        }
+        // The last refresh failed, so the figures above are from an older fetch
+        if let Some(error) = error {
+            secondary_parts.push(error.marker().to_string());
+        }
+        let secondary = if secondary_parts.is_empty() {
+            String::new()
+        } else {
+            format!("· {}", secondary_parts.join(" · "))
+        };
 
         let mut metadata = HashMap::new();
-        metadata.insert("dynamic_icon".to_string(), dynamic_icon);
-        metadata.insert(
-            "five_hour_utilization".to_string(),
-            five_hour_util.to_string(),
-        );
+        if let Some(error) = error {
+            metadata.insert("usage_error".to_string(), error.key().to_string());
+            metadata.insert("usage_stale".to_string(), "true".to_string());
+        }
         metadata.insert(
-            "seven_day_utilization".to_string(),
-            seven_day_util.to_string(),
+            "dynamic_icon".to_string(),
+            Self::get_circle_icon(primary_utilization / 100.0),
         );
+        // Every cached bucket, so bars and custom layouts can use any of them
+        for name in cache.buckets.keys() {
+            let Some(bucket) = cache.bucket(name) else {
+                continue;
+            };
+            if let Some(utilization) = bucket.utilization {
+                metadata.insert(format!("{}_utilization", name), utilization.to_string());
+            }
+            if let Some(resets_at) = bucket.resets_at {
+                metadata.insert(format!("{}_resets_at", name), resets_at);
+            }
+            for (field, value) in bucket.extra {
+                let value = match value {
+                    serde_json::Value::String(text) => text,
+                    serde_json::Value::Number(_) | serde_json::Value::Bool(_) => value.to_string(),
+                    _ => continue,
+                };
+                metadata.insert(format!("{}_{}", name, field), value);
+            }
+        }
 
         Some(SegmentData {
             primary,
@@ -273,3 +695,80 @@ impl Segment for UsageSegment {
         SegmentId::Usage
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, 15, 12, 0, 0).unwrap()
+    }
+
+    fn in_seconds(seconds: i64) -> String {
+        (now() + Duration::seconds(seconds)).to_rfc3339()
+    }
+
+    #[test]
+    fn invalid_strftime_patterns_fall_back_to_relative() {
+        assert_eq!(ResetFormat::from_option("relative"), ResetFormat::Relative);
+        assert_eq!(ResetFormat::from_option("none"), ResetFormat::Hidden);
+        assert_eq!(
+            ResetFormat::from_option("%a %H:%M"),
+            ResetFormat::Strftime("%a %H:%M".to_string())
+        );
+        assert_eq!(ResetFormat::from_option("%Q"), ResetFormat::Relative);
+        assert_eq!(ResetFormat::from_option("%H:%"), ResetFormat::Relative);
+    }
+
+    #[test]
+    fn resets_within_a_minute_show_as_now() {
+        let format = ResetFormat::Relative;
+        let at = |seconds| format.format(Some(&in_seconds(seconds)), now());
+        assert_eq!(at(59).as_deref(), Some("resets now"));
+        assert_eq!(at(-120).as_deref(), Some("resets now"));
+        assert_eq!(at(60).as_deref(), Some("resets in 1m"));
+    }
+
+    #[test]
+    fn strftime_shows_local_time_and_hidden_shows_nothing() {
+        let resets_at = in_seconds(3600);
+        let expected = format!(
+            "resets {}",
+            (now() + Duration::hours(1))
+                .with_timezone(&Local)
+                .format("%H:%M")
+        );
+        assert_eq!(
+            ResetFormat::from_option("%H:%M").format(Some(&resets_at), now()),
+            Some(expected)
+        );
+        assert_eq!(ResetFormat::Hidden.format(Some(&resets_at), now()), None);
+        assert_eq!(ResetFormat::Relative.format(Some("soon"), now()), None);
+        assert_eq!(ResetFormat::Relative.format(None, now()), None);
+    }
+
+    #[test]
+    fn countdown_uses_the_two_largest_units() {
+        assert_eq!(format_countdown(Duration::seconds(13 * 60 + 59)), "13m");
+        assert_eq!(format_countdown(Duration::minutes(60)), "1h0m");
+        assert_eq!(format_countdown(Duration::minutes(2 * 60 + 13)), "2h13m");
+        assert_eq!(format_countdown(Duration::minutes(24 * 60)), "1d0h");
+        assert_eq!(
+            format_countdown(Duration::minutes(3 * 1440 + 4 * 60 + 59)),
+            "3d4h"
+        );
+    }
+
+    #[test]
+    fn window_aliases_name_the_rolling_buckets() {
+        assert_eq!(bucket_name("5h"), "five_hour");
+        assert_eq!(bucket_name("7d"), "seven_day");
+        assert_eq!(bucket_name("seven_day_opus"), "seven_day_opus");
+
+        assert_eq!(bucket_label("five_hour"), "5h");
+        assert_eq!(bucket_label("seven_day"), "7d");
+        assert_eq!(bucket_label("seven_day_opus"), "7d opus");
+        assert_eq!(bucket_label("extra_usage"), "extra");
+    }
+}
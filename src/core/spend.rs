@@ -0,0 +1,630 @@
+//! Token and cost totals across every Claude Code project
+//!
+//! Transcripts under `~/.claude/projects/*/*.jsonl` are indexed into daily
+//! per-model token totals kept in `~/.claude/ccline/.spend_cache.json`. Files
+//! whose size or modification time changed since the last scan are read on
+//! from where the last scan stopped, or from the start when they shrank.
+//! Costs are derived from the totals with the current models.toml pricing, so
+//! pricing edits apply to past spend as well.
+
+use crate::config::ModelConfig;
+use crate::core::transcript::{fnv1a, TranscriptReader, UsageTotals};
+use crate::utils::claude_config;
+use crate::utils::state_file::StateFile;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bump when the cache layout changes so the index is rebuilt
+const CACHE_VERSION: u32 = 2;
+
+/// Totals with their computed cost
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpendRow {
+    pub totals: UsageTotals,
+    pub cost: f64,
+}
+
+impl SpendRow {
+    fn add(&mut self, totals: &UsageTotals, cost: f64) {
+        self.totals.merge(totals);
+        self.cost += cost;
+    }
+}
+
+/// Spend over a date range, broken down several ways
+#[derive(Debug, Default)]
+pub struct SpendReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub total: SpendRow,
+    pub by_project: BTreeMap<String, SpendRow>,
+    pub by_model: BTreeMap<String, SpendRow>,
+    pub by_day: BTreeMap<NaiveDate, SpendRow>,
+    /// Models without pricing; their tokens are counted at no cost
+    pub unpriced_models: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpendPeriod {
+    Today,
+    Week,
+    Month,
+}
+
+impl SpendPeriod {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "today" | "day" | "daily" => Some(Self::Today),
+            "week" | "weekly" => Some(Self::Week),
+            "month" | "monthly" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Today => "today",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+
+    /// First and last day of the period containing `today`; weeks start on Monday
+    pub fn range(&self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            Self::Today => (today, today),
+            Self::Week => {
+                let start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+                (start, start + Duration::days(6))
+            }
+            Self::Month => {
+                let start = today.with_day(1).unwrap_or(today);
+                let next = if start.month() == 12 {
+                    NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+                };
+                (start, next.map(|d| d - Duration::days(1)).unwrap_or(today))
+            }
+        }
+    }
+}
+
+/// The last response of a file, held apart from the daily totals because
+/// more entries of it may still be appended
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LastTurn {
+    id: Option<u64>,
+    day: NaiveDate,
+    model: String,
+    totals: UsageTotals,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FileEntry {
+    size: u64,
+    /// Modification time in nanoseconds since the epoch
+    modified: u64,
+    /// Bytes indexed so far (end of the last complete line)
+    offset: u64,
+    project: String,
+    /// Local date, then model, to totals
+    days: BTreeMap<NaiveDate, BTreeMap<String, UsageTotals>>,
+    last: Option<LastTurn>,
+    /// Hashes of the message IDs counted for this file, so responses copied
+    /// into resumed sessions are only counted once
+    ids: Vec<u64>,
+}
+
+impl FileEntry {
+    fn new(project: String) -> Self {
+        Self {
+            project,
+            ..Default::default()
+        }
+    }
+
+    /// Count the responses written since the last scan
+    fn extend(&mut self, path: &Path, claimed: &mut HashSet<u64>) -> Option<()> {
+        let transcript = TranscriptReader::scan(path, self.offset)?;
+        self.offset = transcript.offset();
+
+        let mut last = self.last.take();
+        for turn in transcript.turns() {
+            let Some(timestamp) = turn.timestamp else {
+                continue;
+            };
+            let id = turn.message_id.as_deref().map(|id| fnv1a(id.as_bytes()));
+            let mut totals = UsageTotals::default();
+            totals.add_usage(&turn.usage);
+
+            // Each content block of a response is written as its own entry,
+            // the last one with the final usage
+            if let Some(pending) = last.as_mut().filter(|last| id.is_some() && last.id == id) {
+                pending.totals = totals;
+                continue;
+            }
+            if let Some(id) = id {
+                if !claimed.insert(id) {
+                    continue;
+                }
+                self.ids.push(id);
+            }
+
+            let turn = LastTurn {
+                id,
+                day: DateTime::<Local>::from(timestamp).date_naive(),
+                model: turn.model.clone().unwrap_or_else(|| "unknown".to_string()),
+                totals,
+            };
+            if let Some(done) = last.replace(turn) {
+                self.days
+                    .entry(done.day)
+                    .or_default()
+                    .entry(done.model)
+                    .or_default()
+                    .merge(&done.totals);
+            }
+        }
+        self.last = last;
+        Some(())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SpendIndex {
+    version: u32,
+    files: BTreeMap<PathBuf, FileEntry>,
+}
+
+fn file_signature(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_nanos() as u64;
+    Some((metadata.len(), modified))
+}
+
+impl SpendIndex {
+    pub fn projects_dir() -> Option<PathBuf> {
+        Some(claude_config::config_dir()?.join("projects"))
+    }
+
+    fn cache_file() -> Option<StateFile> {
+        StateFile::in_state_dir(".spend_cache.json")
+    }
+
+    fn load() -> Self {
+        Self::cache_file()
+            .and_then(|file| file.load::<Self>())
+            .filter(|index| index.version == CACHE_VERSION)
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        if let Some(file) = Self::cache_file() {
+            let _ = file.save(self);
+        }
+    }
+
+    /// Every transcript with its project directory name
+    fn transcripts() -> Vec<(PathBuf, String)> {
+        let Some(projects_dir) = Self::projects_dir() else {
+            return Vec::new();
+        };
+        let Ok(projects) = fs::read_dir(projects_dir) else {
+            return Vec::new();
+        };
+
+        let mut transcripts = Vec::new();
+        for project in projects.flatten() {
+            let project_path = project.path();
+            if !project_path.is_dir() {
+                continue;
+            }
+            let name = project.file_name().to_string_lossy().into_owned();
+            for path in TranscriptReader::project_transcripts(&project_path) {
+                transcripts.push((path, name.clone()));
+            }
+        }
+        transcripts
+    }
+
+    /// Load the index and bring it up to date with the transcripts on disk
+    ///
+    /// Files last modified before `since` are left out, which keeps the first
+    /// scan short when only recent spend is needed.
+    pub fn update(since: Option<SystemTime>) -> Self {
+        let mut index = Self::load();
+        index.version = CACHE_VERSION;
+        if index.refresh(Self::transcripts(), since) {
+            index.save();
+        }
+        index
+    }
+
+    /// Index what changed in `transcripts`, returning whether anything did
+    fn refresh(&mut self, transcripts: Vec<(PathBuf, String)>, since: Option<SystemTime>) -> bool {
+        let mut changed = false;
+
+        let since_nanos = since
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos() as u64);
+
+        let mut stale = Vec::new();
+        let mut present = HashSet::new();
+        for (path, project) in transcripts {
+            let Some((size, modified)) = file_signature(&path) else {
+                continue;
+            };
+            present.insert(path.clone());
+            match self.files.get(&path) {
+                Some(entry) if entry.size == size && entry.modified == modified => {}
+                Some(_) => stale.push((path, project, size, modified)),
+                None if since_nanos.is_some_and(|since| modified < since) => {}
+                None => stale.push((path, project, size, modified)),
+            }
+        }
+
+        let before = self.files.len();
+        self.files.retain(|path, _| present.contains(path));
+        changed |= self.files.len() != before;
+
+        // Oldest first, so original sessions claim responses before resumed copies
+        stale.sort_by_key(|(_, _, _, modified)| *modified);
+        // A file shorter than what was indexed was rewritten; count it again
+        for (path, _, size, _) in &stale {
+            if self
+                .files
+                .get(path)
+                .is_some_and(|entry| *size < entry.offset)
+            {
+                self.files.remove(path);
+            }
+        }
+        let mut claimed: HashSet<u64> = self
+            .files
+            .values()
+            .flat_map(|entry| entry.ids.iter().copied())
+            .collect();
+
+        for (path, project, size, modified) in stale {
+            let entry = self
+                .files
+                .entry(path.clone())
+                .or_insert_with(|| FileEntry::new(project));
+            if entry.extend(&path, &mut claimed).is_some() {
+                entry.size = size;
+                entry.modified = modified;
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    /// Spend between two local dates, inclusive
+    pub fn report(&self, from: NaiveDate, to: NaiveDate, models: &ModelConfig) -> SpendReport {
+        let mut report = SpendReport {
+            from,
+            to,
+            ..Default::default()
+        };
+        let mut unpriced = HashSet::new();
+
+        for entry in self.files.values() {
+            let counted = entry.days.range(from..=to).flat_map(|(day, by_model)| {
+                by_model
+                    .iter()
+                    .map(move |(model, totals)| (day, model, totals))
+            });
+            let last = entry
+                .last
+                .iter()
+                .filter(|last| (from..=to).contains(&last.day))
+                .map(|last| (&last.day, &last.model, &last.totals));
+            for (day, model, totals) in counted.chain(last) {
+                let cost = totals.cost(models, model).unwrap_or_else(|| {
+                    unpriced.insert(model.clone());
+                    0.0
+                });
+                report.total.add(totals, cost);
+                report
+                    .by_project
+                    .entry(entry.project.clone())
+                    .or_default()
+                    .add(totals, cost);
+                report
+                    .by_model
+                    .entry(model.clone())
+                    .or_default()
+                    .add(totals, cost);
+                report.by_day.entry(*day).or_default().add(totals, cost);
+            }
+        }
+
+        report.unpriced_models = unpriced.into_iter().collect();
+        report.unpriced_models.sort();
+        report
+    }
+}
+
+pub fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1_000_000_000 {
+        format!("{:.1}B", tokens as f64 / 1_000_000_000.0)
+    } else if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1000 {
+        format!("{:.1}k", tokens as f64 / 1000.0)
+    } else {
+        tokens.to_string()
+    }
+}
+
+fn push_rows<'a>(
+    out: &mut String,
+    title: &str,
+    rows: impl Iterator<Item = (String, &'a SpendRow)>,
+) {
+    let mut rows: Vec<(String, &SpendRow)> = rows.collect();
+    if rows.is_empty() {
+        return;
+    }
+    rows.sort_by(|a, b| b.1.cost.total_cmp(&a.1.cost));
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+
+    out.push_str(&format!("\n{}\n", title));
+    for (name, row) in rows {
+        out.push_str(&format!(
+            "  {:<width$}  {:>8} tok  {:>6} turns  ${:>9.2}\n",
+            name,
+            format_tokens(row.totals.tokens()),
+            row.totals.turns,
+            row.cost,
+            width = width
+        ));
+    }
+}
+
+/// Plain-text report for `ccline stats`
+pub fn render_report(period: SpendPeriod, days: u32) -> String {
+    let models = ModelConfig::load();
+    let index = SpendIndex::update(None);
+    let today = Local::now().date_naive();
+
+    let mut out = String::new();
+    out.push_str("Spend computed from Claude Code transcripts\n\n");
+    for summary_period in [SpendPeriod::Today, SpendPeriod::Week, SpendPeriod::Month] {
+        let (from, to) = summary_period.range(today);
+        let report = index.report(from, to, models);
+        out.push_str(&format!(
+            "  {:<6} {:>8} tok  ${:>9.2}\n",
+            summary_period.label(),
+            format_tokens(report.total.totals.tokens()),
+            report.total.cost
+        ));
+    }
+
+    let (from, to) = period.range(today);
+    let report = index.report(from, to, models);
+    push_rows(
+        &mut out,
+        &format!("By project ({} {} – {})", period.label(), from, to),
+        report.by_project.iter().map(|(k, v)| (k.clone(), v)),
+    );
+    push_rows(
+        &mut out,
+        &format!("By model ({} {} – {})", period.label(), from, to),
+        report.by_model.iter().map(|(k, v)| (k.clone(), v)),
+    );
+
+    if days > 0 {
+        let from = today - Duration::days(days as i64 - 1);
+        let daily = index.report(from, today, models);
+        out.push_str(&format!("\nDaily (last {} days)\n", days));
+        let mut day = from;
+        while day <= today {
+            let row = daily.by_day.get(&day).copied().unwrap_or_default();
+            out.push_str(&format!(
+                "  {}  {:>8} tok  ${:>9.2}\n",
+                day,
+                format_tokens(row.totals.tokens()),
+                row.cost
+            ));
+            day += Duration::days(1);
+        }
+    }
+
+    if !report.unpriced_models.is_empty() {
+        out.push_str(&format!(
+            "\nNo pricing for: {} (add `pricing` in models.toml)\n",
+            report.unpriced_models.join(", ")
+        ));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::transcript::testing::{assistant, assistant_with_usage};
+    use serde_json::json;
+    use std::io::Write;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ccline-spend-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn append(path: &Path, lines: &[String]) {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+    }
+
+    fn totals(index: &SpendIndex) -> UsageTotals {
+        index
+            .report(date(2000, 1, 1), date(2100, 1, 1), &ModelConfig::default())
+            .total
+            .totals
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        let week = (date(2026, 10, 12), date(2026, 10, 18));
+        assert_eq!(SpendPeriod::Week.range(date(2026, 10, 12)), week);
+        assert_eq!(SpendPeriod::Week.range(date(2026, 10, 18)), week);
+        assert_eq!(
+            SpendPeriod::Week.range(date(2026, 12, 31)),
+            (date(2026, 12, 28), date(2027, 1, 3))
+        );
+    }
+
+    #[test]
+    fn months_end_on_their_last_day() {
+        assert_eq!(
+            SpendPeriod::Month.range(date(2026, 12, 15)),
+            (date(2026, 12, 1), date(2026, 12, 31))
+        );
+        assert_eq!(
+            SpendPeriod::Month.range(date(2028, 2, 10)),
+            (date(2028, 2, 1), date(2028, 2, 29))
+        );
+        assert_eq!(
+            SpendPeriod::Month.range(date(2026, 4, 30)),
+            (date(2026, 4, 1), date(2026, 4, 30))
+        );
+        assert_eq!(
+            SpendPeriod::Today.range(date(2026, 4, 30)),
+            (date(2026, 4, 30), date(2026, 4, 30))
+        );
+    }
+
+    #[test]
+    fn responses_copied_into_resumed_sessions_count_once() {
+        let dir = test_dir("dedup");
+        let original = dir.join("original.jsonl");
+        append(
+            &original,
+            &[
+                assistant("msg_1", 0, 100, &[]),
+                assistant("msg_2", 1, 200, &[]),
+            ],
+        );
+        let resumed = dir.join("resumed.jsonl");
+        append(
+            &resumed,
+            &[
+                assistant("msg_1", 0, 100, &[]),
+                assistant("msg_2", 1, 200, &[]),
+                assistant("msg_3", 2, 300, &[]),
+            ],
+        );
+        let transcripts = vec![
+            (original, "project".to_string()),
+            (resumed.clone(), "project".to_string()),
+        ];
+
+        let mut index = SpendIndex::default();
+        assert!(index.refresh(transcripts.clone(), None));
+        let counted = totals(&index);
+        assert_eq!(counted.turns, 3);
+        assert_eq!(counted.input_tokens, 600);
+
+        // Resuming again copies the history once more
+        append(
+            &resumed,
+            &[
+                assistant("msg_1", 0, 100, &[]),
+                assistant("msg_4", 3, 400, &[]),
+            ],
+        );
+        assert!(index.refresh(transcripts, None));
+        let counted = totals(&index);
+        assert_eq!(counted.turns, 4);
+        assert_eq!(counted.input_tokens, 1000);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn appended_lines_are_read_from_the_last_offset() {
+        let dir = test_dir("append");
+        let path = dir.join("session.jsonl");
+        let usage = |output: u32| json!({"input_tokens": 100, "output_tokens": output});
+        append(&path, &[assistant_with_usage("msg_1", 0, usage(1), &[])]);
+        let transcripts = vec![(path.clone(), "project".to_string())];
+
+        let mut index = SpendIndex::default();
+        assert!(index.refresh(transcripts.clone(), None));
+        assert_eq!(totals(&index).output_tokens, 1);
+        assert!(!index.refresh(transcripts.clone(), None));
+
+        // A later entry of the same response carries its final usage
+        append(
+            &path,
+            &[
+                assistant_with_usage("msg_1", 0, usage(50), &[]),
+                assistant("msg_2", 1, 200, &[]),
+            ],
+        );
+        assert!(index.refresh(transcripts, None));
+        let counted = totals(&index);
+        assert_eq!(counted.turns, 2);
+        assert_eq!(counted.input_tokens, 300);
+        assert_eq!(counted.output_tokens, 60);
+        assert_eq!(
+            index.files[&path].offset,
+            fs::metadata(&path).unwrap().len()
+        );
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn shrunk_files_are_indexed_again() {
+        let dir = test_dir("shrink");
+        let path = dir.join("session.jsonl");
+        append(
+            &path,
+            &[
+                assistant("msg_1", 0, 100, &[]),
+                assistant("msg_2", 1, 200, &[]),
+            ],
+        );
+        let transcripts = vec![(path.clone(), "project".to_string())];
+
+        let mut index = SpendIndex::default();
+        index.refresh(transcripts.clone(), None);
+        assert_eq!(totals(&index).turns, 2);
+
+        fs::remove_file(&path).unwrap();
+        append(&path, &[assistant("msg_9", 2, 50, &[])]);
+        assert!(index.refresh(transcripts, None));
+        let counted = totals(&index);
+        assert_eq!(counted.turns, 1);
+        assert_eq!(counted.input_tokens, 50);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -8,7 +8,19 @@ fn visible_width(text: &str) -> usize {
     let mut chars = text.chars().peekable();
 
     while let Some(ch) = chars.next() {
-        if ch == '\x1b' {
+        if ch == '\x1b' && chars.peek() == Some(&']') {
+            // OSC sequence (e.g. OSC 8 hyperlink), terminated by BEL or ESC \
+            chars.next();
+            while let Some(c) = chars.next() {
+                if c == '\x07' {
+                    break;
+                }
+                if c == '\x1b' && chars.peek() == Some(&'\\') {
+                    chars.next();
+                    break;
+                }
+            }
+        } else if ch == '\x1b' {
             // Start of ANSI escape sequence
             in_escape = true;
             // Skip the [ character
@@ -416,13 +428,13 @@ impl StatusLineGenerator {
             (Some(prev), Some(curr)) => {
                 // Arrow foreground = previous segment's background
                 // Arrow background = current segment's background
-                let fg_code = self.color_to_foreground_code(prev);
+                let fg_code = prev.fg_sequence();
                 let bg_code = self.apply_background_color(curr);
                 format!("{}{}{}\x1b[0m", bg_code, fg_code, arrow_char)
             }
             (Some(prev), None) => {
                 // Previous segment has background, current doesn't
-                let fg_code = self.color_to_foreground_code(prev);
+                let fg_code = prev.fg_sequence();
                 format!("{}{}\x1b[0m", fg_code, arrow_char)
             }
             (None, Some(curr)) => {
@@ -436,22 +448,6 @@ impl StatusLineGenerator {
             }
         }
     }
-
-    /// Convert AnsiColor to foreground color code
-    fn color_to_foreground_code(&self, color: &AnsiColor) -> String {
-        match color {
-            AnsiColor::Color16 { c16 } => {
-                let code = if *c16 < 8 { 30 + c16 } else { 90 + (c16 - 8) };
-                format!("\x1b[{}m", code)
-            }
-            AnsiColor::Color256 { c256 } => {
-                format!("\x1b[38;5;{}m", c256)
-            }
-            AnsiColor::Rgb { r, g, b } => {
-                format!("\x1b[38;2;{};{};{}m", r, g, b)
-            }
-        }
-    }
 }
 
 pub fn collect_all_segments(
@@ -461,6 +457,10 @@ pub fn collect_all_segments(
     use crate::core::segments::*;
 
     let mut results = Vec::new();
+    let budgets = config
+        .budget
+        .as_ref()
+        .map(|budget| (budget, crate::core::budget::evaluate(budget, input)));
 
     for segment_config in &config.segments {
         // Skip disabled segments to avoid unnecessary API requests
@@ -483,19 +483,159 @@ pub fn collect_all_segments(
                     .get("show_sha")
                     .and_then(|v| v.as_bool())
                     .unwrap_or(false);
-                let segment = GitSegment::new().with_sha(show_sha);
+                let show_untracked = segment_config
+                    .options
+                    .get("show_untracked")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
+                let timeout_ms = segment_config
+                    .options
+                    .get("status_timeout_ms")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(500);
+                let cache_duration = segment_config
+                    .options
+                    .get("cache_duration")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(5);
+                let hyperlink = segment_config
+                    .options
+                    .get("hyperlink")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let hyperlink_target = segment_config
+                    .options
+                    .get("hyperlink_target")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("branch");
+                let forge_hosts = segment_config
+                    .options
+                    .get("forge_hosts")
+                    .and_then(|v| v.as_object())
+                    .map(|hosts| {
+                        hosts
+                            .iter()
+                            .filter_map(|(host, value)| {
+                                Some((host.clone(), git::ForgeHost::from_option(value)?))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let link_target = hyperlink.then(|| git::LinkTarget::from_name(hyperlink_target));
+                let ticket = segment_config
+                    .options
+                    .get("ticket_pattern")
+                    .and_then(|v| v.as_str())
+                    .and_then(|pattern| regex::Regex::new(pattern).ok())
+                    .map(|pattern| git::TicketConfig {
+                        pattern,
+                        url_template: segment_config
+                            .options
+                            .get("ticket_url")
+                            .and_then(|v| v.as_str())
+                            .map(|s| s.to_string()),
+                        replace_branch: segment_config
+                            .options
+                            .get("ticket_only")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false),
+                        color: segment_config
+                            .options
+                            .get("ticket_color")
+                            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+                    });
+                let show_commit_age = segment_config
+                    .options
+                    .get("show_commit_age")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let segment = GitSegment::new()
+                    .with_sha(show_sha)
+                    .with_untracked(show_untracked)
+                    .with_timeout(std::time::Duration::from_millis(timeout_ms))
+                    .with_cache_duration(cache_duration)
+                    .with_hyperlink(link_target)
+                    .with_forge_hosts(forge_hosts)
+                    .with_ticket(ticket)
+                    .with_commit_age(show_commit_age);
                 segment.collect(input)
             }
             crate::config::SegmentId::ContextWindow => {
-                let segment = ContextWindowSegment::new();
+                let show_breakdown = segment_config
+                    .options
+                    .get("show_breakdown")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let show_compact_headroom = segment_config
+                    .options
+                    .get("show_compact_headroom")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let sparkline = segment_config
+                    .options
+                    .get("show_sparkline")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false)
+                    .then(|| {
+                        let mut sparkline = SparklineConfig::default();
+                        if let Some(turns) = segment_config
+                            .options
+                            .get("sparkline_turns")
+                            .and_then(|v| v.as_u64())
+                        {
+                            sparkline.turns = turns.clamp(1, 200) as usize;
+                        }
+                        sparkline.relative = segment_config
+                            .options
+                            .get("sparkline_scale")
+                            .and_then(|v| v.as_str())
+                            == Some("window");
+                        if let Some(marker) = segment_config
+                            .options
+                            .get("sparkline_marker")
+                            .and_then(|v| v.as_str())
+                        {
+                            sparkline.marker = marker.to_string();
+                        }
+                        sparkline
+                    });
+                let segment = ContextWindowSegment::new()
+                    .with_breakdown(show_breakdown)
+                    .with_compact_headroom(show_compact_headroom)
+                    .with_sparkline(sparkline);
                 segment.collect(input)
             }
             crate::config::SegmentId::Usage => {
-                let segment = UsageSegment::new();
+                let windows = segment_config
+                    .options
+                    .get("windows")
+                    .and_then(|v| v.as_array())
+                    .map(|names| {
+                        names
+                            .iter()
+                            .filter_map(|v| v.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let reset_format = segment_config
+                    .options
+                    .get("reset_format")
+                    .and_then(|v| v.as_str())
+                    .map(usage::ResetFormat::from_option)
+                    .unwrap_or_default();
+                let segment = UsageSegment::new()
+                    .with_windows(windows)
+                    .with_reset_format(reset_format);
                 segment.collect(input)
             }
             crate::config::SegmentId::Cost => {
-                let segment = CostSegment::new();
+                let source = segment_config
+                    .options
+                    .get("cost_source")
+                    .and_then(|v| v.as_str())
+                    .and_then(cost::CostSource::from_name)
+                    .unwrap_or_default();
+                let segment = CostSegment::new().with_source(source);
                 segment.collect(input)
             }
             crate::config::SegmentId::Session => {
@@ -510,10 +650,75 @@ pub fn collect_all_segments(
                 let segment = UpdateSegment::new();
                 segment.collect(input)
             }
+            crate::config::SegmentId::Spend => {
+                let period = segment_config
+                    .options
+                    .get("period")
+                    .and_then(|v| v.as_str())
+                    .and_then(crate::core::spend::SpendPeriod::from_name)
+                    .unwrap_or(crate::core::spend::SpendPeriod::Today);
+                let refresh_interval = segment_config
+                    .options
+                    .get("refresh_interval")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(60);
+                let segment = SpendSegment::new()
+                    .with_period(period)
+                    .with_refresh_interval(refresh_interval);
+                segment.collect(input)
+            }
+            crate::config::SegmentId::BurnRate => {
+                let window_minutes = segment_config
+                    .options
+                    .get("window_minutes")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(15)
+                    .clamp(1, 300);
+                let show_cost = segment_config
+                    .options
+                    .get("show_cost")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
+                let segment = BurnRateSegment::new()
+                    .with_window(chrono::Duration::minutes(window_minutes as i64))
+                    .with_cost(show_cost);
+                segment.collect(input)
+            }
+            crate::config::SegmentId::CacheEfficiency => {
+                let show_savings = segment_config
+                    .options
+                    .get("show_savings")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
+                let segment = CacheEfficiencySegment::new().with_savings(show_savings);
+                segment.collect(input)
+            }
+            crate::config::SegmentId::Plan => {
+                let show_account = segment_config
+                    .options
+                    .get("show_account")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
+                let segment = PlanSegment::new()
+                    .with_credentials(config.credentials.clone().unwrap_or_default())
+                    .with_account(show_account);
+                segment.collect(input)
+            }
         };
 
-        if let Some(data) = segment_data {
-            results.push((segment_config.clone(), data));
+        if let Some(mut data) = segment_data {
+            let mut segment_config = segment_config.clone();
+            if segment_config.id == crate::config::SegmentId::Model {
+                // Per-model colors from models.toml
+                if let Some(entry) = crate::config::ModelConfig::load().find(&input.model.id) {
+                    entry.apply_colors(&mut segment_config.colors);
+                }
+            }
+            crate::core::bar::apply_bar(&segment_config, &mut data);
+            if let Some((budget, statuses)) = &budgets {
+                crate::core::budget::apply_budget(budget, statuses, &mut segment_config, &mut data);
+            }
+            results.push((segment_config, data));
         }
     }
 
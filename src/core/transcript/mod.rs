@@ -0,0 +1,941 @@
+//! Shared, incrementally parsed view of Claude Code transcripts
+//!
+//! Every segment that needs transcript data goes through [`TranscriptReader`].
+//! A transcript is parsed at most once per process (i.e. once per render) and
+//! its derived state is persisted under `~/.claude/ccline/transcripts/`, so
+//! later renders only read the bytes appended since the previous one. The
+//! state keeps running counters and the most recent turns only, and a first
+//! load reads backwards from the end until it has enough turns.
+
+pub mod model;
+#[cfg(test)]
+pub(crate) mod testing;
+
+use crate::config::{ModelConfig, NormalizedUsage};
+use crate::utils::reverse_reader::ReverseLineReader;
+use crate::utils::state_file::{write_atomic, StateFile};
+use chrono::{DateTime, Utc};
+use model::{ContentBlock, TranscriptEntry};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Bump when the persisted layout changes so old state is rebuilt
+const STATE_VERSION: u32 = 2;
+
+/// Turns kept in memory and on disk; older ones only count toward the totals
+const RECENT_TURNS: usize = 200;
+
+/// State files untouched for this long are removed
+const STATE_RETENTION_SECS: u64 = 7 * 24 * 3600;
+
+/// Token counts of one assistant response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TurnUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub total_tokens: u32,
+    pub cache_creation_input_tokens: u32,
+    pub cache_read_input_tokens: u32,
+}
+
+impl From<&NormalizedUsage> for TurnUsage {
+    fn from(usage: &NormalizedUsage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            total_tokens: usage.total_tokens,
+            cache_creation_input_tokens: usage.cache_creation_input_tokens,
+            cache_read_input_tokens: usage.cache_read_input_tokens,
+        }
+    }
+}
+
+impl TurnUsage {
+    pub fn normalized(&self) -> NormalizedUsage {
+        NormalizedUsage {
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            total_tokens: self.total_tokens,
+            cache_creation_input_tokens: self.cache_creation_input_tokens,
+            cache_read_input_tokens: self.cache_read_input_tokens,
+            ..Default::default()
+        }
+    }
+
+    /// Tokens occupying the context window after this turn
+    pub fn context_tokens(&self) -> u32 {
+        self.normalized().display_tokens()
+    }
+}
+
+/// Token counts summed over many responses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub turns: u64,
+}
+
+impl UsageTotals {
+    pub fn add_usage(&mut self, usage: &TurnUsage) {
+        self.input_tokens += usage.input_tokens as u64;
+        self.output_tokens += usage.output_tokens as u64;
+        self.cache_creation_tokens += usage.cache_creation_input_tokens as u64;
+        self.cache_read_tokens += usage.cache_read_input_tokens as u64;
+        self.turns += 1;
+    }
+
+    pub fn merge(&mut self, other: &UsageTotals) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.turns += other.turns;
+    }
+
+    pub fn tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_creation_tokens + self.cache_read_tokens
+    }
+
+    /// Cost under the model's pricing; `None` when the model has none
+    pub fn cost(&self, models: &ModelConfig, model_id: &str) -> Option<f64> {
+        models.get_pricing(model_id).map(|pricing| {
+            pricing.cost_for(
+                self.input_tokens,
+                self.output_tokens,
+                self.cache_creation_tokens,
+                self.cache_read_tokens,
+            )
+        })
+    }
+}
+
+/// One assistant API response (entries sharing a message id are merged)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Turn {
+    pub message_id: Option<String>,
+    pub uuid: Option<String>,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub model: Option<String>,
+    pub usage: TurnUsage,
+    /// Written by a subagent rather than the main conversation
+    pub sidechain: bool,
+    /// Names of tools requested in this response
+    pub tool_uses: Vec<String>,
+}
+
+/// A context compaction (manual `/compact` or automatic)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Compaction {
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Number of turns recorded before the compaction, counted over the
+    /// whole session
+    pub turn_index: usize,
+    pub trigger: Option<String>,
+    pub pre_tokens: Option<u64>,
+}
+
+/// Context size recorded for one main-conversation turn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContextSample {
+    pub tokens: u32,
+    /// The context was compacted between the previous sample and this one
+    pub after_compaction: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct TranscriptState {
+    version: u32,
+    /// Bytes consumed so far (end of the last complete line)
+    offset: u64,
+    /// Bytes before this were skipped by a first load that read backwards;
+    /// counters and totals only cover the rest until they are read
+    head_end: u64,
+    session_id: Option<String>,
+    /// The most recent turns
+    turns: Vec<Turn>,
+    /// Turns no longer kept in `turns`
+    dropped_turns: usize,
+    /// Usage of the dropped turns by model ("" when the model is unknown)
+    dropped_usage: BTreeMap<String, UsageTotals>,
+    compactions: Vec<Compaction>,
+    first_timestamp: Option<DateTime<Utc>>,
+    last_timestamp: Option<DateTime<Utc>>,
+    user_messages: u32,
+    tool_results: u32,
+    tool_errors: u32,
+    /// leafUuid when the last line is a summary
+    summary_leaf: Option<String>,
+    /// Leaf that `summary_usage` was resolved for
+    resolved_leaf: Option<String>,
+    summary_usage: Option<TurnUsage>,
+    /// Keep every turn instead of the most recent ones
+    #[serde(skip)]
+    keep_all: bool,
+}
+
+/// Message id of an assistant response that is recorded as a turn, or `None`
+/// when the entry is not one
+fn turn_id(entry: &TranscriptEntry) -> Option<Option<String>> {
+    if !entry.is_type("assistant") || entry.is_api_error {
+        return None;
+    }
+    let message = entry.message.as_ref()?;
+    if message.model.as_deref() == Some("<synthetic>") || message.usage.is_none() {
+        return None;
+    }
+    Some(message.id.clone().or_else(|| entry.request_id.clone()))
+}
+
+impl TranscriptState {
+    fn ingest(&mut self, entry: TranscriptEntry) {
+        self.summary_leaf = entry.summary_leaf().map(|s| s.to_string());
+
+        if self.session_id.is_none() {
+            self.session_id = entry.session_id.clone();
+        }
+
+        let timestamp = entry
+            .timestamp
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&Utc));
+        if let Some(ts) = timestamp {
+            if self.first_timestamp.is_none() {
+                self.first_timestamp = Some(ts);
+            }
+            self.last_timestamp = Some(ts);
+        }
+
+        if entry.is_compaction() {
+            // A boundary and its summary message describe the same compaction
+            let already_recorded = self
+                .compactions
+                .last()
+                .is_some_and(|c| c.turn_index == self.turn_count());
+            if !already_recorded {
+                self.compactions.push(Compaction {
+                    timestamp,
+                    turn_index: self.turn_count(),
+                    trigger: entry
+                        .compact_metadata
+                        .as_ref()
+                        .and_then(|m| m.trigger.clone()),
+                    pre_tokens: entry.compact_metadata.as_ref().and_then(|m| m.pre_tokens),
+                });
+            }
+        }
+
+        if entry.is_type("user") {
+            let mut results = 0;
+            for block in entry.blocks() {
+                if let ContentBlock::ToolResult { is_error, .. } = block {
+                    results += 1;
+                    if is_error.unwrap_or(false) {
+                        self.tool_errors += 1;
+                    }
+                }
+            }
+            self.tool_results += results;
+            if results == 0 && !entry.is_compact_summary {
+                self.user_messages += 1;
+            }
+            return;
+        }
+
+        let Some(message_id) = turn_id(&entry) else {
+            return;
+        };
+        let Some(message) = entry.message.as_ref() else {
+            return;
+        };
+        let Some(raw_usage) = message.usage.clone() else {
+            return;
+        };
+
+        let usage = TurnUsage::from(&raw_usage.normalize());
+        let tool_uses: Vec<String> = entry
+            .blocks()
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse { name, .. } => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+        // Each content block of a response is written as its own entry
+        if let Some(last) = self.turns.last_mut() {
+            if message_id.is_some() && last.message_id == message_id {
+                last.usage = usage;
+                last.tool_uses.extend(tool_uses);
+                last.uuid = entry.uuid.clone();
+                return;
+            }
+        }
+
+        self.turns.push(Turn {
+            message_id,
+            uuid: entry.uuid.clone(),
+            timestamp,
+            model: message.model.clone(),
+            usage,
+            sidechain: entry.is_sidechain,
+            tool_uses,
+        });
+        if !self.keep_all && self.turns.len() > RECENT_TURNS {
+            let dropped = self.turns.remove(0);
+            self.dropped_usage
+                .entry(dropped.model.unwrap_or_default())
+                .or_default()
+                .add_usage(&dropped.usage);
+            self.dropped_turns += 1;
+        }
+    }
+
+    fn turn_count(&self) -> usize {
+        self.dropped_turns + self.turns.len()
+    }
+
+    fn usage_by_model(&self) -> BTreeMap<String, UsageTotals> {
+        let mut usage = self.dropped_usage.clone();
+        for turn in &self.turns {
+            usage
+                .entry(turn.model.clone().unwrap_or_default())
+                .or_default()
+                .add_usage(&turn.usage);
+        }
+        usage
+    }
+
+    /// First load: read backwards just far enough for the most recent turns
+    /// and leave the bytes before them for [`complete`](Self::complete)
+    fn seed(path: &Path) -> Option<Self> {
+        let mut reader = ReverseLineReader::open(path).ok()?;
+        let mut state = Self {
+            version: STATE_VERSION,
+            ..Default::default()
+        };
+        // The remainder after the final newline, or a last line still being
+        // written; reading resumes from there next time
+        let Some(last) = reader.next() else {
+            return Some(state);
+        };
+        state.offset = last.ok()?.0;
+
+        let mut entries = Vec::new();
+        let mut turns = 0;
+        let mut earliest_id = None;
+        let mut start = state.offset;
+        for item in reader {
+            let (offset, line) = item.ok()?;
+            let text = line.trim();
+            if let Ok(entry) = serde_json::from_str::<TranscriptEntry>(text) {
+                if let Some(id) = turn_id(&entry) {
+                    // Entries of one response merge into the turn after them
+                    let same_turn = id.is_some() && id == earliest_id;
+                    if !same_turn {
+                        if turns == RECENT_TURNS {
+                            break;
+                        }
+                        turns += 1;
+                    }
+                    earliest_id = id;
+                }
+                entries.push(entry);
+            }
+            start = offset;
+        }
+
+        state.head_end = start;
+        for entry in entries.into_iter().rev() {
+            state.ingest(entry);
+        }
+        Some(state)
+    }
+
+    /// Read the bytes a seeded first load skipped, so counters and totals
+    /// cover the whole session
+    fn complete(&mut self, path: &Path) -> Option<()> {
+        if self.head_end == 0 {
+            return Some(());
+        }
+        let mut head = Self::default();
+        head.read_from(path, self.head_end)?;
+
+        let head_turns = head.turn_count();
+        for (model, usage) in head.usage_by_model() {
+            self.dropped_usage.entry(model).or_default().merge(&usage);
+        }
+        self.dropped_turns += head_turns;
+
+        for compaction in &mut self.compactions {
+            compaction.turn_index += head_turns;
+        }
+        let mut compactions = std::mem::take(&mut head.compactions);
+        compactions.append(&mut self.compactions);
+        // A boundary and its summary may sit on either side of `head_end`
+        compactions.dedup_by_key(|c| c.turn_index);
+        self.compactions = compactions;
+
+        self.session_id = head.session_id.or(self.session_id.take());
+        self.first_timestamp = head.first_timestamp.or(self.first_timestamp);
+        self.last_timestamp = self.last_timestamp.or(head.last_timestamp);
+        self.user_messages += head.user_messages;
+        self.tool_results += head.tool_results;
+        self.tool_errors += head.tool_errors;
+        self.head_end = 0;
+        Some(())
+    }
+
+    /// Parse lines from `offset` up to `end`, stopping at the last complete line
+    fn read_from(&mut self, path: &Path, end: u64) -> Option<()> {
+        let mut file = fs::File::open(path).ok()?;
+        file.seek(SeekFrom::Start(self.offset)).ok()?;
+        let mut reader = BufReader::new(file);
+        let mut line = Vec::new();
+
+        while self.offset < end {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line).ok()?;
+            // An unterminated final line is still being written; re-read it later
+            if read == 0 || line.last() != Some(&b'\n') {
+                break;
+            }
+            self.offset += read as u64;
+
+            let text = String::from_utf8_lossy(&line);
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            if let Ok(entry) = serde_json::from_str::<TranscriptEntry>(text) {
+                self.ingest(entry);
+            }
+        }
+
+        Some(())
+    }
+}
+
+/// Parsed transcript with query helpers for segments
+#[derive(Debug)]
+pub struct Transcript {
+    path: PathBuf,
+    state: TranscriptState,
+}
+
+impl Transcript {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn session_id(&self) -> Option<&str> {
+        self.state.session_id.as_deref()
+    }
+
+    /// The most recent assistant responses with usage, including subagent
+    /// ones; every response for a [`TranscriptReader::scan`]
+    pub fn turns(&self) -> &[Turn] {
+        &self.state.turns
+    }
+
+    /// Bytes read so far, up to the end of the last complete line
+    pub fn offset(&self) -> u64 {
+        self.state.offset
+    }
+
+    /// Number of responses in the session, including ones older than `turns`
+    pub fn turn_count(&self) -> usize {
+        self.state.turn_count()
+    }
+
+    /// Recent responses made since the last compaction
+    pub fn turns_since_compaction(&self) -> &[Turn] {
+        let since = self
+            .state
+            .compactions
+            .last()
+            .map(|c| c.turn_index.saturating_sub(self.state.dropped_turns))
+            .unwrap_or(0);
+        &self.state.turns[since.min(self.state.turns.len())..]
+    }
+
+    /// Token totals by model ("" when a response named none); covers the
+    /// whole session when loaded with [`TranscriptReader::load_complete`]
+    pub fn usage_by_model(&self) -> BTreeMap<String, UsageTotals> {
+        self.state.usage_by_model()
+    }
+
+    /// Assistant responses of the main conversation
+    pub fn main_turns(&self) -> impl Iterator<Item = &Turn> {
+        self.state.turns.iter().filter(|t| !t.sidechain)
+    }
+
+    pub fn last_turn(&self) -> Option<&Turn> {
+        self.state.turns.iter().rev().find(|t| !t.sidechain)
+    }
+
+    pub fn compactions(&self) -> &[Compaction] {
+        &self.state.compactions
+    }
+
+    /// Context size after each recent main-conversation turn, oldest first
+    pub fn context_history(&self) -> Vec<ContextSample> {
+        let dropped = self.state.dropped_turns;
+        let mut compactions = self
+            .state
+            .compactions
+            .iter()
+            .filter(|c| c.turn_index >= dropped)
+            .peekable();
+        let mut samples = Vec::new();
+        let mut compacted = false;
+
+        for (index, turn) in self.state.turns.iter().enumerate() {
+            while compactions
+                .next_if(|c| c.turn_index <= dropped + index)
+                .is_some()
+            {
+                compacted = true;
+            }
+            if turn.sidechain {
+                continue;
+            }
+            samples.push(ContextSample {
+                tokens: turn.usage.context_tokens(),
+                after_compaction: std::mem::take(&mut compacted),
+            });
+        }
+
+        samples
+    }
+
+    pub fn first_timestamp(&self) -> Option<DateTime<Utc>> {
+        self.state.first_timestamp
+    }
+
+    pub fn last_timestamp(&self) -> Option<DateTime<Utc>> {
+        self.state.last_timestamp
+    }
+
+    /// Prompts typed by the user (tool results excluded)
+    pub fn user_messages(&self) -> u32 {
+        self.state.user_messages
+    }
+
+    pub fn tool_results(&self) -> u32 {
+        self.state.tool_results
+    }
+
+    pub fn tool_errors(&self) -> u32 {
+        self.state.tool_errors
+    }
+
+    /// Usage that currently occupies the context window
+    ///
+    /// A transcript ending in a summary continues an earlier conversation, so
+    /// the usage of the summarized leaf message is returned instead.
+    pub fn context_usage(&self) -> Option<TurnUsage> {
+        if self.state.summary_leaf.is_some() {
+            self.state.summary_usage
+        } else {
+            self.last_turn().map(|t| t.usage)
+        }
+    }
+}
+
+/// Entry point for loading transcripts
+pub struct TranscriptReader;
+
+fn loaded() -> &'static Mutex<HashMap<PathBuf, Arc<Transcript>>> {
+    static LOADED: OnceLock<Mutex<HashMap<PathBuf, Arc<Transcript>>>> = OnceLock::new();
+    LOADED.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+impl TranscriptReader {
+    /// Load a transcript, reusing the copy parsed earlier in this process
+    pub fn load<P: AsRef<Path>>(path: P) -> Option<Arc<Transcript>> {
+        let path = path.as_ref().to_path_buf();
+
+        if let Some(transcript) = loaded().lock().ok()?.get(&path) {
+            return Some(transcript.clone());
+        }
+
+        let transcript = Arc::new(Self::parse(&path)?);
+        if let Ok(mut cache) = loaded().lock() {
+            cache.insert(path, transcript.clone());
+        }
+        Some(transcript)
+    }
+
+    /// Read a transcript from `offset` to the end without touching its saved
+    /// state or the process-wide cache
+    ///
+    /// For bulk scans over many files where each is only needed once; summary
+    /// leaves are left unresolved since that searches the whole project.
+    pub fn scan<P: AsRef<Path>>(path: P, offset: u64) -> Option<Transcript> {
+        let path = path.as_ref();
+        let mut state = TranscriptState {
+            version: STATE_VERSION,
+            offset,
+            keep_all: true,
+            ..Default::default()
+        };
+        state.read_from(path, u64::MAX)?;
+        Some(Transcript {
+            path: path.to_path_buf(),
+            state,
+        })
+    }
+
+    /// Load a transcript with counters and totals covering the whole session
+    ///
+    /// A first load only reads the most recent turns; the rest of the file is
+    /// read here once and saved, so later renders start from complete state.
+    pub fn load_complete<P: AsRef<Path>>(path: P) -> Option<Arc<Transcript>> {
+        let path = path.as_ref();
+        let transcript = Self::load(path)?;
+        if transcript.state.head_end == 0 {
+            return Some(transcript);
+        }
+
+        let mut state = transcript.state.clone();
+        state.complete(path)?;
+        if let Some(state_path) = Self::state_path(path) {
+            Self::save_state(&state_path, &state, false);
+        }
+        let transcript = Arc::new(Transcript {
+            path: path.to_path_buf(),
+            state,
+        });
+        if let Ok(mut cache) = loaded().lock() {
+            cache.insert(path.to_path_buf(), transcript.clone());
+        }
+        Some(transcript)
+    }
+
+    /// Context usage for a session, falling back to the most recent
+    /// transcript in the project when the given file does not exist yet
+    pub fn context_usage<P: AsRef<Path>>(transcript_path: P) -> Option<TurnUsage> {
+        let path = transcript_path.as_ref();
+
+        if let Some(transcript) = Self::load(path) {
+            return transcript.context_usage();
+        }
+
+        if !path.exists() {
+            for session_path in Self::project_transcripts(path.parent()?) {
+                if let Some(usage) = Self::load(&session_path).and_then(|t| t.context_usage()) {
+                    return Some(usage);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// `.jsonl` files in a project directory, most recently modified first
+    pub fn project_transcripts(project_dir: &Path) -> Vec<PathBuf> {
+        let mut session_files: Vec<PathBuf> = fs::read_dir(project_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().and_then(|s| s.to_str()) == Some("jsonl"))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        session_files.sort_by_key(|path| {
+            std::cmp::Reverse(
+                fs::metadata(path)
+                    .and_then(|m| m.modified())
+                    .unwrap_or(std::time::UNIX_EPOCH),
+            )
+        });
+        session_files
+    }
+
+    fn parse(path: &Path) -> Option<Transcript> {
+        let len = fs::metadata(path).ok()?.len();
+        let state_path = Self::state_path(path);
+
+        let previous = state_path
+            .as_ref()
+            .and_then(|p| StateFile::new(p).load::<TranscriptState>())
+            // A shorter file than we consumed means it was rewritten
+            .filter(|s| s.version == STATE_VERSION && s.offset <= len);
+
+        let mut state = match previous.clone() {
+            Some(state) => state,
+            None => TranscriptState::seed(path)?,
+        };
+
+        if state.offset < len {
+            state.read_from(path, u64::MAX)?;
+        }
+
+        if let Some(leaf_uuid) = state.summary_leaf.clone() {
+            if state.resolved_leaf.as_ref() != Some(&leaf_uuid) {
+                state.summary_usage = path
+                    .parent()
+                    .and_then(|project_dir| find_usage_by_leaf_uuid(&leaf_uuid, project_dir));
+                state.resolved_leaf = Some(leaf_uuid);
+            }
+        }
+
+        if previous.as_ref() != Some(&state) {
+            if let Some(state_path) = state_path {
+                Self::save_state(&state_path, &state, previous.is_none());
+            }
+        }
+
+        Some(Transcript {
+            path: path.to_path_buf(),
+            state,
+        })
+    }
+
+    fn state_dir() -> Option<PathBuf> {
+        let home = dirs::home_dir()?;
+        Some(home.join(".claude").join("ccline").join("transcripts"))
+    }
+
+    /// One state file per transcript, named after the file stem plus a
+    /// stable hash of the full path
+    fn state_path(transcript_path: &Path) -> Option<PathBuf> {
+        let stem = transcript_path.file_stem()?.to_string_lossy();
+        let hash = fnv1a(transcript_path.to_string_lossy().as_bytes());
+        Some(Self::state_dir()?.join(format!("{}-{:016x}.json", stem, hash)))
+    }
+
+    fn save_state(state_path: &Path, state: &TranscriptState, is_new: bool) {
+        if let Some(parent) = state_path.parent() {
+            let _ = fs::create_dir_all(parent);
+            // Only sweep old files when a new transcript shows up
+            if is_new {
+                Self::prune_states(parent);
+            }
+        }
+        // Each transcript belongs to one session, so overlapping renders only
+        // need the rename to keep readers from seeing a partial file
+        if let Ok(json) = serde_json::to_vec(state) {
+            let _ = write_atomic(state_path, &json);
+        }
+    }
+
+    fn prune_states(dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let expired = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.elapsed().ok())
+                .is_some_and(|age| age.as_secs() > STATE_RETENTION_SECS);
+            if expired {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+fn find_usage_by_leaf_uuid(leaf_uuid: &str, project_dir: &Path) -> Option<TurnUsage> {
+    // Search for the leafUuid across all session files in the project directory
+    for path in TranscriptReader::project_transcripts(project_dir) {
+        if let Some(usage) = search_uuid_in_file(&path, leaf_uuid) {
+            return Some(usage);
+        }
+    }
+
+    None
+}
+
+/// Scan a transcript backwards for `target_uuid`
+///
+/// A user message points at its parent, which always appears earlier in the
+/// file, so the same backwards pass can follow it to the assistant message.
+fn search_uuid_in_file(path: &Path, target_uuid: &str) -> Option<TurnUsage> {
+    let reader = ReverseLineReader::open(path).ok()?;
+    let mut target = target_uuid.to_string();
+    let mut following_parent = false;
+
+    for item in reader {
+        let (_, line) = item.ok()?;
+        let line = line.trim();
+        if line.is_empty() || !line.contains(target.as_str()) {
+            continue;
+        }
+
+        let Ok(entry) = serde_json::from_str::<TranscriptEntry>(line) else {
+            continue;
+        };
+        if entry.uuid.as_deref() != Some(target.as_str()) {
+            continue;
+        }
+
+        if entry.is_type("assistant") {
+            // Direct assistant message with usage
+            let raw_usage = entry.message?.usage?;
+            return Some(TurnUsage::from(&raw_usage.normalize()));
+        } else if entry.is_type("user") && !following_parent {
+            // User message, need to find the parent assistant message
+            target = entry.parent_uuid?;
+            following_parent = true;
+        } else {
+            return None;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::{assistant, boundary, compact_summary, transcript, user};
+    use super::*;
+
+    fn write_transcript(name: &str, lines: &[String], tail: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "ccline-transcript-{}-{}.jsonl",
+            name,
+            std::process::id()
+        ));
+        let mut text = lines.join("\n");
+        text.push('\n');
+        text.push_str(tail);
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn read_all(path: &Path) -> TranscriptState {
+        let mut state = TranscriptState {
+            version: STATE_VERSION,
+            ..Default::default()
+        };
+        state.read_from(path, u64::MAX).unwrap();
+        state
+    }
+
+    #[test]
+    fn entries_of_one_response_merge_into_one_turn() {
+        let path = write_transcript(
+            "merge",
+            &[
+                assistant("msg_1", 0, 100, &["Read"]),
+                assistant("msg_1", 0, 120, &["Bash"]),
+                user("next"),
+                assistant("msg_2", 1, 200, &[]),
+            ],
+            "",
+        );
+        let state = read_all(&path);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(state.turns.len(), 2);
+        assert_eq!(state.turns[0].usage.input_tokens, 120);
+        assert_eq!(state.turns[0].tool_uses, ["Read", "Bash"]);
+        assert_eq!(state.turns[1].message_id.as_deref(), Some("msg_2"));
+        assert_eq!(state.user_messages, 1);
+    }
+
+    #[test]
+    fn boundary_and_summary_record_one_compaction() {
+        let transcript = transcript(&[
+            assistant("msg_1", 0, 100, &[]),
+            assistant("msg_2", 1, 200, &[]),
+            boundary(),
+            compact_summary(),
+            assistant("msg_3", 2, 50, &[]),
+        ]);
+
+        let compactions = transcript.compactions();
+        assert_eq!(compactions.len(), 1);
+        assert_eq!(compactions[0].turn_index, 2);
+        assert_eq!(compactions[0].trigger.as_deref(), Some("auto"));
+        assert_eq!(compactions[0].pre_tokens, Some(150000));
+        assert_eq!(transcript.user_messages(), 0);
+
+        let history = transcript.context_history();
+        let marked: Vec<bool> = history.iter().map(|s| s.after_compaction).collect();
+        assert_eq!(marked, [false, false, true]);
+        assert_eq!(transcript.turns_since_compaction().len(), 1);
+    }
+
+    #[test]
+    fn old_turns_only_count_toward_totals() {
+        let lines: Vec<String> = (0..RECENT_TURNS as u32 + 50)
+            .map(|i| assistant(&format!("msg_{}", i), i, 100, &["Read"]))
+            .collect();
+        let path = write_transcript("bounded", &lines, "");
+        let state = read_all(&path);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(state.turns.len(), RECENT_TURNS);
+        assert_eq!(state.turn_count(), RECENT_TURNS + 50);
+        assert_eq!(state.turns[0].message_id.as_deref(), Some("msg_50"));
+        let usage = state.usage_by_model();
+        assert_eq!(usage["claude-sonnet-4"].turns, RECENT_TURNS as u64 + 50);
+        assert_eq!(
+            usage["claude-sonnet-4"].input_tokens,
+            100 * (RECENT_TURNS as u64 + 50)
+        );
+    }
+
+    #[test]
+    fn seeded_load_completes_to_a_full_read() {
+        let mut lines = Vec::new();
+        for i in 0..RECENT_TURNS as u32 + 50 {
+            if i == 20 || i == 230 {
+                lines.push(boundary());
+                lines.push(compact_summary());
+            }
+            lines.push(user("go"));
+            lines.push(assistant(&format!("msg_{}", i), i, 100 + i, &[]));
+            lines.push(assistant(&format!("msg_{}", i), i, 100 + i, &["Edit"]));
+        }
+        // A final line still being written is left for the next read
+        let path = write_transcript("seeded", &lines, r#"{"type":"assist"#);
+
+        let mut seeded = TranscriptState::seed(&path).unwrap();
+        assert!(seeded.head_end > 0);
+        assert_eq!(seeded.turns.len(), RECENT_TURNS);
+        assert_eq!(seeded.compactions.len(), 1);
+        assert_eq!(seeded.compactions[0].turn_index, 180);
+
+        seeded.complete(&path).unwrap();
+        let full = read_all(&path);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(seeded, full);
+        assert_eq!(full.compactions[1].turn_index, 230);
+        assert_eq!(full.user_messages, RECENT_TURNS as u32 + 50);
+    }
+
+    #[test]
+    fn short_transcript_is_read_whole_on_first_load() {
+        let path = write_transcript("short", &[user("hi"), assistant("msg_1", 0, 100, &[])], "");
+        let seeded = TranscriptState::seed(&path).unwrap();
+        let full = read_all(&path);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(seeded.head_end, 0);
+        assert_eq!(seeded, full);
+    }
+}
@@ -0,0 +1,143 @@
+//! Serde model for Claude Code transcript JSONL lines
+//!
+//! Only the fields statusline segments care about are modelled; everything
+//! else in an entry is ignored during deserialization.
+
+use crate::config::RawUsage;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    User,
+    Assistant,
+    System,
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    ToolUse {
+        #[serde(default)]
+        id: String,
+        #[serde(default)]
+        name: String,
+    },
+    ToolResult {
+        #[serde(default)]
+        tool_use_id: String,
+        #[serde(default)]
+        is_error: Option<bool>,
+    },
+    /// Text, thinking, images and anything newer
+    #[serde(other)]
+    Other,
+}
+
+/// Message content is either a bare string or a list of blocks
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+impl MessageContent {
+    pub fn blocks(&self) -> &[ContentBlock] {
+        match self {
+            MessageContent::Text(_) => &[],
+            MessageContent::Blocks(blocks) => blocks,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Message {
+    pub id: Option<String>,
+    pub role: Option<Role>,
+    pub model: Option<String>,
+    /// Unrecognised content shapes are dropped rather than failing the entry
+    #[serde(default, deserialize_with = "lenient")]
+    pub content: Option<MessageContent>,
+    pub usage: Option<RawUsage>,
+}
+
+fn lenient<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(T::deserialize(value).ok())
+}
+
+/// Metadata attached to `compact_boundary` system entries
+#[derive(Debug, Deserialize)]
+pub struct CompactMetadata {
+    pub trigger: Option<String>,
+    #[serde(rename = "preTokens")]
+    pub pre_tokens: Option<u64>,
+}
+
+/// One line of a transcript file
+#[derive(Debug, Deserialize)]
+pub struct TranscriptEntry {
+    pub r#type: Option<String>,
+    pub subtype: Option<String>,
+    pub message: Option<Message>,
+    #[serde(rename = "leafUuid")]
+    pub leaf_uuid: Option<String>,
+    pub uuid: Option<String>,
+    #[serde(rename = "parentUuid")]
+    pub parent_uuid: Option<String>,
+    pub summary: Option<String>,
+    /// RFC 3339 timestamp
+    pub timestamp: Option<String>,
+    #[serde(rename = "requestId")]
+    pub request_id: Option<String>,
+    /// Set on entries written by subagents (Task tool)
+    #[serde(rename = "isSidechain", default)]
+    pub is_sidechain: bool,
+    /// Set on the user message that carries a compaction summary
+    #[serde(rename = "isCompactSummary", default)]
+    pub is_compact_summary: bool,
+    /// Synthetic assistant entries recording API failures
+    #[serde(rename = "isApiErrorMessage", default)]
+    pub is_api_error: bool,
+    #[serde(rename = "compactMetadata")]
+    pub compact_metadata: Option<CompactMetadata>,
+    #[serde(rename = "sessionId")]
+    pub session_id: Option<String>,
+}
+
+impl TranscriptEntry {
+    pub fn is_type(&self, kind: &str) -> bool {
+        self.r#type.as_deref() == Some(kind)
+    }
+
+    /// Summary entries that point at the leaf of a previous conversation
+    pub fn summary_leaf(&self) -> Option<&str> {
+        if self.is_type("summary") {
+            self.leaf_uuid.as_deref()
+        } else {
+            None
+        }
+    }
+
+    /// Marks a context compaction, either as a boundary or a summary message
+    pub fn is_compaction(&self) -> bool {
+        (self.is_type("system") && self.subtype.as_deref() == Some("compact_boundary"))
+            || self.is_compact_summary
+    }
+
+    pub fn blocks(&self) -> &[ContentBlock] {
+        self.message
+            .as_ref()
+            .and_then(|m| m.content.as_ref())
+            .map(|c| c.blocks())
+            .unwrap_or(&[])
+    }
+}
@@ -0,0 +1,78 @@
+//! Transcript lines and parsed transcripts for unit tests
+
+use super::model::TranscriptEntry;
+use super::{Transcript, TranscriptState, STATE_VERSION};
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+/// Time of the test session's `minute`th minute
+pub fn at(minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, 1, minute / 60, minute % 60, 0)
+        .unwrap()
+}
+
+/// Assistant entry with the given raw usage
+pub fn assistant_with_usage(id: &str, minute: u32, usage: Value, tools: &[&str]) -> String {
+    let mut content = vec![json!({"type": "text", "text": "ok"})];
+    content.extend(
+        tools
+            .iter()
+            .map(|name| json!({"type": "tool_use", "id": "t", "name": name})),
+    );
+    json!({
+        "type": "assistant",
+        "sessionId": "session",
+        "timestamp": at(minute).to_rfc3339(),
+        "message": {
+            "id": id,
+            "model": "claude-sonnet-4",
+            "content": content,
+            "usage": usage,
+        },
+    })
+    .to_string()
+}
+
+/// Assistant entry with `input` context tokens and 10 output tokens
+pub fn assistant(id: &str, minute: u32, input: u32, tools: &[&str]) -> String {
+    assistant_with_usage(
+        id,
+        minute,
+        json!({"input_tokens": input, "output_tokens": 10}),
+        tools,
+    )
+}
+
+pub fn user(text: &str) -> String {
+    json!({"type": "user", "message": {"role": "user", "content": text}}).to_string()
+}
+
+pub fn boundary() -> String {
+    json!({
+        "type": "system",
+        "subtype": "compact_boundary",
+        "compactMetadata": {"trigger": "auto", "preTokens": 150000},
+    })
+    .to_string()
+}
+
+pub fn compact_summary() -> String {
+    json!({"type": "user", "isCompactSummary": true, "message": {"role": "user", "content": "summary"}})
+        .to_string()
+}
+
+/// Transcript parsed from `lines` without touching the file system
+pub fn transcript(lines: &[String]) -> Transcript {
+    let mut state = TranscriptState {
+        version: STATE_VERSION,
+        ..Default::default()
+    };
+    for line in lines {
+        state.ingest(serde_json::from_str::<TranscriptEntry>(line).unwrap());
+    }
+    Transcript {
+        path: PathBuf::from("session.jsonl"),
+        state,
+    }
+}
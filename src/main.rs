@@ -1,4 +1,4 @@
-use ccometixline::cli::Cli;
+use ccometixline::cli::{Cli, Command};
 use ccometixline::config::{Config, InputData};
 use ccometixline::core::{collect_all_segments, StatusLineGenerator};
 use std::io::{self, IsTerminal};
@@ -6,6 +6,27 @@ use std::io::{self, IsTerminal};
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse_args();
 
+    if let Some(Command::Stats { period, days }) = &cli.command {
+        use ccometixline::core::spend::{render_report, SpendPeriod};
+
+        let Some(period) = SpendPeriod::from_name(period) else {
+            eprintln!("Unknown period '{}', expected today, week or month", period);
+            std::process::exit(2);
+        };
+        print!("{}", render_report(period, *days));
+        return Ok(());
+    }
+
+    if let Some(Command::RefreshUsage) = &cli.command {
+        ccometixline::core::segments::UsageSegment::refresh_cache();
+        return Ok(());
+    }
+
+    if let Some(Command::RefreshSpend) = &cli.command {
+        ccometixline::core::segments::SpendSegment::refresh_summary();
+        return Ok(());
+    }
+
     // Handle configuration commands
     if cli.init {
         use ccometixline::config::InitResult;
@@ -91,9 +112,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load configuration
     let mut config = Config::load().unwrap_or_else(|_| Config::default());
 
-    // Apply theme override if provided
+    // Apply theme override if provided, keeping budgets, credentials and network
+    // settings from the config file
     if let Some(theme) = cli.theme {
+        let budget = config.budget.take();
+        let credentials = config.credentials.take();
+        let network = config.network.take();
         config = ccometixline::ui::themes::ThemePresets::get_theme(&theme);
+        config.budget = budget;
+        config.credentials = credentials;
+        config.network = network;
     }
 
     // Check if stdin has data
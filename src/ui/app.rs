@@ -477,6 +477,10 @@ impl App {
                         SegmentId::Session => "Session",
                         SegmentId::OutputStyle => "Output Style",
                         SegmentId::Update => "Update",
+                        SegmentId::BurnRate => "Burn Rate",
+                        SegmentId::Spend => "Spend",
+                        SegmentId::CacheEfficiency => "Cache Efficiency",
+                        SegmentId::Plan => "Plan",
                     };
                     let is_enabled = segment.enabled;
                     self.status_message = Some(format!(
@@ -504,6 +508,10 @@ impl App {
                                 SegmentId::Session => "Session",
                                 SegmentId::OutputStyle => "Output Style",
                                 SegmentId::Update => "Update",
+                                SegmentId::BurnRate => "Burn Rate",
+                                SegmentId::Spend => "Spend",
+                                SegmentId::CacheEfficiency => "Cache Efficiency",
+                                SegmentId::Plan => "Plan",
                             };
                             let is_enabled = segment.enabled;
                             self.status_message = Some(format!(
@@ -608,7 +616,14 @@ impl App {
     }
 
     fn switch_to_theme(&mut self, theme_name: &str) {
+        // Budgets, credentials and network settings are not part of a theme
+        let budget = self.config.budget.take();
+        let credentials = self.config.credentials.take();
+        let network = self.config.network.take();
         self.config = crate::ui::themes::ThemePresets::get_theme(theme_name);
+        self.config.budget = budget;
+        self.config.credentials = credentials;
+        self.config.network = network;
         self.selected_segment = 0;
         self.preview.update_preview(&self.config);
         self.status_message = Some(format!("Loaded {} theme (unsaved)", theme_name));
@@ -195,8 +195,8 @@ impl PreviewComponent {
                     },
                 },
                 SegmentId::Usage => SegmentData {
-                    primary: "24%".to_string(),
-                    secondary: "· 10-7-2".to_string(),
+                    primary: "5h 24%".to_string(),
+                    secondary: "· resets in 2h13m · 7d 41% · resets in 3d4h".to_string(),
                     metadata: HashMap::new(),
                 },
                 SegmentId::Cost => SegmentData {
@@ -228,6 +228,47 @@ impl PreviewComponent {
                         map
                     },
                 },
+                SegmentId::BurnRate => SegmentData {
+                    primary: "~25m left".to_string(),
+                    secondary: "· context · 4.2k/min · $1.80/h".to_string(),
+                    metadata: {
+                        let mut map = HashMap::new();
+                        map.insert("tokens_per_minute".to_string(), "4200".to_string());
+                        map.insert("context_minutes_left".to_string(), "25".to_string());
+                        map.insert("limit".to_string(), "context".to_string());
+                        map
+                    },
+                },
+                SegmentId::Spend => SegmentData {
+                    primary: "today $12.40".to_string(),
+                    secondary: "· 18.2M tok".to_string(),
+                    metadata: {
+                        let mut map = HashMap::new();
+                        map.insert("today_cost".to_string(), "12.40".to_string());
+                        map.insert("period".to_string(), "today".to_string());
+                        map
+                    },
+                },
+                SegmentId::CacheEfficiency => SegmentData {
+                    primary: "92% hit".to_string(),
+                    secondary: "· session 85% · saved $3.10".to_string(),
+                    metadata: {
+                        let mut map = HashMap::new();
+                        map.insert("last_hit_percent".to_string(), "92.0".to_string());
+                        map.insert("session_hit_percent".to_string(), "85.0".to_string());
+                        map
+                    },
+                },
+                SegmentId::Plan => SegmentData {
+                    primary: "Max".to_string(),
+                    secondary: "· OAuth · dev@example.com".to_string(),
+                    metadata: {
+                        let mut map = HashMap::new();
+                        map.insert("billing".to_string(), "oauth".to_string());
+                        map.insert("plan".to_string(), "Max".to_string());
+                        map
+                    },
+                },
                 SegmentId::Update => SegmentData {
                     primary: format!("v{}", env!("CARGO_PKG_VERSION")),
                     secondary: "".to_string(),
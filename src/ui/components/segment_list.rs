@@ -67,6 +67,10 @@ impl SegmentListComponent {
                     SegmentId::Session => "Session",
                     SegmentId::OutputStyle => "Output Style",
                     SegmentId::Update => "Update",
+                    SegmentId::BurnRate => "Burn Rate",
+                    SegmentId::Spend => "Spend",
+                    SegmentId::CacheEfficiency => "Cache Efficiency",
+                    SegmentId::Plan => "Plan",
                 };
 
                 if is_selected {
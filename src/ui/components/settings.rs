@@ -36,6 +36,10 @@ impl SettingsComponent {
                 SegmentId::Session => "Session",
                 SegmentId::OutputStyle => "Output Style",
                 SegmentId::Update => "Update",
+                SegmentId::BurnRate => "Burn Rate",
+                SegmentId::Spend => "Spend",
+                SegmentId::CacheEfficiency => "Cache Efficiency",
+                SegmentId::Plan => "Plan",
             };
             let current_icon = match config.style.mode {
                 StyleMode::Plain => &segment.icon.plain,
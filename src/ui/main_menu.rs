@@ -98,10 +98,8 @@ impl MainMenu {
                 }
 
                 match key.code {
-                    KeyCode::Up => {
-                        if self.selected_item > 0 {
-                            self.selected_item -= 1;
-                        }
+                    KeyCode::Up if self.selected_item > 0 => {
+                        self.selected_item -= 1;
                     }
                     KeyCode::Down => {
                         let menu_items = self.get_menu_items();
@@ -247,7 +245,7 @@ impl MainMenu {
         let target_dir = target_path.parent().unwrap();
 
         // Create directory if needed
-        if let Err(e) = std::fs::create_dir_all(&target_dir) {
+        if let Err(e) = std::fs::create_dir_all(target_dir) {
             self.status_message = Some(StatusMessage {
                 message: format!("✗ Failed to create directory: {}", e),
                 is_error: true,
@@ -60,11 +60,8 @@ impl ThemePresets {
         let themes_dir = Self::get_themes_path();
         let theme_path = themes_dir.join(format!("{}.toml", theme_name));
 
-        // Create themes directory if it doesn't exist
-        std::fs::create_dir_all(&themes_dir)?;
-
         let content = toml::to_string_pretty(config)?;
-        std::fs::write(&theme_path, content)?;
+        crate::utils::state_file::write_atomic(&theme_path, content.as_bytes())?;
 
         Ok(())
     }
@@ -126,11 +123,18 @@ impl ThemePresets {
                 theme_cometix::git_segment(),
                 theme_cometix::context_window_segment(),
                 theme_cometix::usage_segment(),
+                theme_cometix::burn_rate_segment(),
+                theme_cometix::spend_segment(),
+                theme_cometix::cache_efficiency_segment(),
+                theme_cometix::plan_segment(),
                 theme_cometix::cost_segment(),
                 theme_cometix::session_segment(),
                 theme_cometix::output_style_segment(),
             ],
             theme: "cometix".to_string(),
+            budget: None,
+            credentials: None,
+            network: None,
         }
     }
 
@@ -146,11 +150,18 @@ impl ThemePresets {
                 theme_default::git_segment(),
                 theme_default::context_window_segment(),
                 theme_default::usage_segment(),
+                theme_default::burn_rate_segment(),
+                theme_default::spend_segment(),
+                theme_default::cache_efficiency_segment(),
+                theme_default::plan_segment(),
                 theme_default::cost_segment(),
                 theme_default::session_segment(),
                 theme_default::output_style_segment(),
             ],
             theme: "default".to_string(),
+            budget: None,
+            credentials: None,
+            network: None,
         }
     }
 
@@ -166,11 +177,18 @@ impl ThemePresets {
                 theme_minimal::git_segment(),
                 theme_minimal::context_window_segment(),
                 theme_minimal::usage_segment(),
+                theme_minimal::burn_rate_segment(),
+                theme_minimal::spend_segment(),
+                theme_minimal::cache_efficiency_segment(),
+                theme_minimal::plan_segment(),
                 theme_minimal::cost_segment(),
                 theme_minimal::session_segment(),
                 theme_minimal::output_style_segment(),
             ],
             theme: "minimal".to_string(),
+            budget: None,
+            credentials: None,
+            network: None,
         }
     }
 
@@ -186,11 +204,18 @@ impl ThemePresets {
                 theme_gruvbox::git_segment(),
                 theme_gruvbox::context_window_segment(),
                 theme_gruvbox::usage_segment(),
+                theme_gruvbox::burn_rate_segment(),
+                theme_gruvbox::spend_segment(),
+                theme_gruvbox::cache_efficiency_segment(),
+                theme_gruvbox::plan_segment(),
                 theme_gruvbox::cost_segment(),
                 theme_gruvbox::session_segment(),
                 theme_gruvbox::output_style_segment(),
             ],
             theme: "gruvbox".to_string(),
+            budget: None,
+            credentials: None,
+            network: None,
         }
     }
 
@@ -206,11 +231,18 @@ impl ThemePresets {
                 theme_nord::git_segment(),
                 theme_nord::context_window_segment(),
                 theme_nord::usage_segment(),
+                theme_nord::burn_rate_segment(),
+                theme_nord::spend_segment(),
+                theme_nord::cache_efficiency_segment(),
+                theme_nord::plan_segment(),
                 theme_nord::cost_segment(),
                 theme_nord::session_segment(),
                 theme_nord::output_style_segment(),
             ],
             theme: "nord".to_string(),
+            budget: None,
+            credentials: None,
+            network: None,
         }
     }
 
@@ -226,11 +258,18 @@ impl ThemePresets {
                 theme_powerline_dark::git_segment(),
                 theme_powerline_dark::context_window_segment(),
                 theme_powerline_dark::usage_segment(),
+                theme_powerline_dark::burn_rate_segment(),
+                theme_powerline_dark::spend_segment(),
+                theme_powerline_dark::cache_efficiency_segment(),
+                theme_powerline_dark::plan_segment(),
                 theme_powerline_dark::cost_segment(),
                 theme_powerline_dark::session_segment(),
                 theme_powerline_dark::output_style_segment(),
             ],
             theme: "powerline-dark".to_string(),
+            budget: None,
+            credentials: None,
+            network: None,
         }
     }
 
@@ -246,11 +285,18 @@ impl ThemePresets {
                 theme_powerline_light::git_segment(),
                 theme_powerline_light::context_window_segment(),
                 theme_powerline_light::usage_segment(),
+                theme_powerline_light::burn_rate_segment(),
+                theme_powerline_light::spend_segment(),
+                theme_powerline_light::cache_efficiency_segment(),
+                theme_powerline_light::plan_segment(),
                 theme_powerline_light::cost_segment(),
                 theme_powerline_light::session_segment(),
                 theme_powerline_light::output_style_segment(),
             ],
             theme: "powerline-light".to_string(),
+            budget: None,
+            credentials: None,
+            network: None,
         }
     }
 
@@ -266,11 +312,18 @@ impl ThemePresets {
                 theme_powerline_rose_pine::git_segment(),
                 theme_powerline_rose_pine::context_window_segment(),
                 theme_powerline_rose_pine::usage_segment(),
+                theme_powerline_rose_pine::burn_rate_segment(),
+                theme_powerline_rose_pine::spend_segment(),
+                theme_powerline_rose_pine::cache_efficiency_segment(),
+                theme_powerline_rose_pine::plan_segment(),
                 theme_powerline_rose_pine::cost_segment(),
                 theme_powerline_rose_pine::session_segment(),
                 theme_powerline_rose_pine::output_style_segment(),
             ],
             theme: "powerline-rose-pine".to_string(),
+            budget: None,
+            credentials: None,
+            network: None,
         }
     }
 
@@ -286,11 +339,18 @@ impl ThemePresets {
                 theme_powerline_tokyo_night::git_segment(),
                 theme_powerline_tokyo_night::context_window_segment(),
                 theme_powerline_tokyo_night::usage_segment(),
+                theme_powerline_tokyo_night::burn_rate_segment(),
+                theme_powerline_tokyo_night::spend_segment(),
+                theme_powerline_tokyo_night::cache_efficiency_segment(),
+                theme_powerline_tokyo_night::plan_segment(),
                 theme_powerline_tokyo_night::cost_segment(),
                 theme_powerline_tokyo_night::session_segment(),
                 theme_powerline_tokyo_night::output_style_segment(),
             ],
             theme: "powerline-tokyo-night".to_string(),
+            budget: None,
+            credentials: None,
+            network: None,
         }
     }
 }
@@ -162,3 +162,75 @@ pub fn usage_segment() -> SegmentConfig {
         },
     }
 }
+
+pub fn burn_rate_segment() -> SegmentConfig {
+    SegmentConfig {
+        id: SegmentId::BurnRate,
+        enabled: false,
+        icon: IconConfig {
+            plain: "🔥".to_string(),
+            nerd_font: "\u{f0238}".to_string(), // fire
+        },
+        colors: ColorConfig {
+            icon: Some(AnsiColor::Color16 { c16: 9 }),
+            text: Some(AnsiColor::Color16 { c16: 9 }),
+            background: None,
+        },
+        styles: TextStyleConfig::default(),
+        options: HashMap::new(),
+    }
+}
+
+pub fn spend_segment() -> SegmentConfig {
+    SegmentConfig {
+        id: SegmentId::Spend,
+        enabled: false,
+        icon: IconConfig {
+            plain: "📅".to_string(),
+            nerd_font: "\u{f00ed}".to_string(), // calendar
+        },
+        colors: ColorConfig {
+            icon: Some(AnsiColor::Color256 { c256: 214 }), // Gruvbox yellow
+            text: Some(AnsiColor::Color256 { c256: 214 }),
+            background: None,
+        },
+        styles: TextStyleConfig { text_bold: true },
+        options: HashMap::new(),
+    }
+}
+
+pub fn cache_efficiency_segment() -> SegmentConfig {
+    SegmentConfig {
+        id: SegmentId::CacheEfficiency,
+        enabled: false,
+        icon: IconConfig {
+            plain: "♻️".to_string(),
+            nerd_font: "\u{f1b8}".to_string(), // recycle
+        },
+        colors: ColorConfig {
+            icon: Some(AnsiColor::Color256 { c256: 142 }), // Gruvbox green
+            text: Some(AnsiColor::Color256 { c256: 142 }),
+            background: None,
+        },
+        styles: TextStyleConfig { text_bold: true },
+        options: HashMap::new(),
+    }
+}
+
+pub fn plan_segment() -> SegmentConfig {
+    SegmentConfig {
+        id: SegmentId::Plan,
+        enabled: false,
+        icon: IconConfig {
+            plain: "🪪".to_string(),
+            nerd_font: "\u{f2c2}".to_string(), // id card
+        },
+        colors: ColorConfig {
+            icon: Some(AnsiColor::Color256 { c256: 109 }), // Gruvbox cyan
+            text: Some(AnsiColor::Color256 { c256: 109 }),
+            background: None,
+        },
+        styles: TextStyleConfig { text_bold: true },
+        options: HashMap::new(),
+    }
+}
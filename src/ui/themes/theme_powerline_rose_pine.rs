@@ -258,3 +258,123 @@ pub fn usage_segment() -> SegmentConfig {
         },
     }
 }
+
+pub fn burn_rate_segment() -> SegmentConfig {
+    SegmentConfig {
+        id: SegmentId::BurnRate,
+        enabled: false,
+        icon: IconConfig {
+            plain: "🔥".to_string(),
+            nerd_font: "\u{f0238}".to_string(), // fire
+        },
+        colors: ColorConfig {
+            icon: Some(AnsiColor::Rgb {
+                r: 246,
+                g: 193,
+                b: 119,
+            }),
+            text: Some(AnsiColor::Rgb {
+                r: 246,
+                g: 193,
+                b: 119,
+            }),
+            background: Some(AnsiColor::Rgb {
+                r: 35,
+                g: 33,
+                b: 54,
+            }),
+        },
+        styles: TextStyleConfig::default(),
+        options: HashMap::new(),
+    }
+}
+
+pub fn spend_segment() -> SegmentConfig {
+    SegmentConfig {
+        id: SegmentId::Spend,
+        enabled: false,
+        icon: IconConfig {
+            plain: "📅".to_string(),
+            nerd_font: "\u{f00ed}".to_string(), // calendar
+        },
+        colors: ColorConfig {
+            icon: Some(AnsiColor::Rgb {
+                r: 246,
+                g: 193,
+                b: 119,
+            }),
+            text: Some(AnsiColor::Rgb {
+                r: 246,
+                g: 193,
+                b: 119,
+            }),
+            background: Some(AnsiColor::Rgb {
+                r: 35,
+                g: 33,
+                b: 54,
+            }), // Rose Pine dark background
+        },
+        styles: TextStyleConfig::default(),
+        options: HashMap::new(),
+    }
+}
+
+pub fn cache_efficiency_segment() -> SegmentConfig {
+    SegmentConfig {
+        id: SegmentId::CacheEfficiency,
+        enabled: false,
+        icon: IconConfig {
+            plain: "♻️".to_string(),
+            nerd_font: "\u{f1b8}".to_string(), // recycle
+        },
+        colors: ColorConfig {
+            icon: Some(AnsiColor::Rgb {
+                r: 156,
+                g: 207,
+                b: 216,
+            }),
+            text: Some(AnsiColor::Rgb {
+                r: 156,
+                g: 207,
+                b: 216,
+            }),
+            background: Some(AnsiColor::Rgb {
+                r: 42,
+                g: 39,
+                b: 63,
+            }), // Rose Pine darker background
+        },
+        styles: TextStyleConfig::default(),
+        options: HashMap::new(),
+    }
+}
+
+pub fn plan_segment() -> SegmentConfig {
+    SegmentConfig {
+        id: SegmentId::Plan,
+        enabled: false,
+        icon: IconConfig {
+            plain: "🪪".to_string(),
+            nerd_font: "\u{f2c2}".to_string(), // id card
+        },
+        colors: ColorConfig {
+            icon: Some(AnsiColor::Rgb {
+                r: 49,
+                g: 116,
+                b: 143,
+            }),
+            text: Some(AnsiColor::Rgb {
+                r: 49,
+                g: 116,
+                b: 143,
+            }),
+            background: Some(AnsiColor::Rgb {
+                r: 38,
+                g: 35,
+                b: 58,
+            }), // Rose Pine darkest background
+        },
+        styles: TextStyleConfig::default(),
+        options: HashMap::new(),
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "self-update")]
+use crate::utils::state_file::StateFile;
 #[cfg(feature = "self-update")]
 use chrono::{DateTime, Utc};
 
@@ -77,28 +79,12 @@ impl UpdateState {
     pub fn load() -> Self {
         #[cfg(feature = "self-update")]
         {
-            let config_dir = dirs::home_dir()
-                .unwrap_or_default()
-                .join(".claude")
-                .join("ccline");
-
-            let state_file = config_dir.join(".update_state.json");
-
-            let mut state = if let Ok(content) = std::fs::read_to_string(&state_file) {
-                if let Ok(state) = serde_json::from_str::<UpdateState>(&content) {
-                    state
-                } else {
-                    UpdateState {
-                        current_version: env!("CARGO_PKG_VERSION").to_string(),
-                        ..Default::default()
-                    }
-                }
-            } else {
-                UpdateState {
+            let mut state = Self::state_file()
+                .and_then(|file| file.load())
+                .unwrap_or_else(|| UpdateState {
                     current_version: env!("CARGO_PKG_VERSION").to_string(),
                     ..Default::default()
-                }
-            };
+                });
 
             // Trigger background update check if needed
             if state.should_check_update() {
@@ -191,22 +177,18 @@ impl UpdateState {
     /// Save update state to config directory
     pub fn save(&self) -> Result<(), std::io::Error> {
         #[cfg(feature = "self-update")]
-        {
-            let config_dir = dirs::home_dir()
-                .unwrap_or_default()
-                .join(".claude")
-                .join("ccline");
-
-            std::fs::create_dir_all(&config_dir)?;
-            let state_file = config_dir.join(".update_state.json");
-
-            let content = serde_json::to_string_pretty(self)?;
-            std::fs::write(&state_file, content)?;
+        if let Some(file) = Self::state_file() {
+            file.save(self)?;
         }
 
         Ok(())
     }
 
+    #[cfg(feature = "self-update")]
+    fn state_file() -> Option<StateFile> {
+        StateFile::in_state_dir(".update_state.json")
+    }
+
     /// Check if update check should be triggered
     #[cfg(feature = "self-update")]
     pub fn should_check_update(&self) -> bool {
@@ -237,7 +219,12 @@ impl UpdateState {
 /// GitHub Release API response structures
 #[cfg(feature = "self-update")]
 pub mod github {
+    use crate::utils::http::NetworkPolicy;
     use serde::{Deserialize, Serialize};
+    use std::time::Duration;
+
+    /// Timeout of the release check unless `network.timeout` is set
+    const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
 
     #[derive(Debug, Deserialize, Serialize, Clone)]
     pub struct GitHubRelease {
@@ -352,7 +339,10 @@ pub mod github {
     pub fn check_for_updates() -> Result<Option<GitHubRelease>, Box<dyn std::error::Error>> {
         let url = "https://api.github.com/repos/Haleclipse/CCometixLine/releases/latest";
 
-        let response = ureq::get(url)
+        let network = NetworkPolicy::load();
+        let agent = network.agent(url, network.timeout_or(CHECK_TIMEOUT))?;
+        let response = agent
+            .get(url)
             .set(
                 "User-Agent",
                 &format!("CCometixLine/{}", env!("CARGO_PKG_VERSION")),
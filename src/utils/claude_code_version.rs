@@ -0,0 +1,152 @@
+//! Installed Claude Code version, for the User-Agent of API requests
+//!
+//! Resolved without touching the network: the version Claude Code reports in
+//! the statusline input is remembered in `~/.claude/ccline/.claude_code_version.json`,
+//! and when no render has reported one yet the local installation is inspected.
+
+use crate::utils::state_file::StateFile;
+use crate::utils::ClaudeCodePatcher;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VersionCache {
+    /// Version last reported in the statusline input
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reported: Option<String>,
+    /// Version read from a local installation, keyed by the file it came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    installed: Option<InstalledVersion>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct InstalledVersion {
+    version: String,
+    path: PathBuf,
+    /// Modification time of `path` in seconds since the epoch
+    modified: u64,
+}
+
+impl VersionCache {
+    fn file() -> Option<StateFile> {
+        StateFile::in_state_dir(".claude_code_version.json")
+    }
+
+    fn load() -> Self {
+        Self::file()
+            .and_then(|file| file.load())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) {
+        if let Some(file) = Self::file() {
+            let _ = file.save(self);
+        }
+    }
+}
+
+/// Remember the version from the statusline input; only writes when it changed
+pub fn remember(version: &str) {
+    let version = version.trim();
+    if version.is_empty() {
+        return;
+    }
+    let mut cache = VersionCache::load();
+    if cache.reported.as_deref() != Some(version) {
+        cache.reported = Some(version.to_string());
+        cache.save();
+    }
+}
+
+/// The installed Claude Code version, if it can be determined locally
+pub fn installed_version() -> Option<String> {
+    let mut cache = VersionCache::load();
+    if let Some(version) = cache.reported {
+        return Some(version);
+    }
+
+    let path = find_installation()?;
+    let modified = modified_secs(&path)?;
+    if let Some(installed) = &cache.installed {
+        if installed.path == path && installed.modified == modified {
+            return Some(installed.version.clone());
+        }
+    }
+
+    let version = read_version(&path)?;
+    cache.installed = Some(InstalledVersion {
+        version: version.clone(),
+        path,
+        modified,
+    });
+    cache.save();
+    Some(version)
+}
+
+/// User-Agent Claude Code itself sends, e.g. `claude-code/2.0.14`
+pub fn user_agent() -> String {
+    match installed_version() {
+        Some(version) => format!("claude-code/{}", version),
+        None => "claude-code".to_string(),
+    }
+}
+
+/// `cli.js` of an npm installation, or the binary of a native one
+fn find_installation() -> Option<PathBuf> {
+    // `claude` on PATH is usually a symlink to cli.js or a versioned binary
+    let on_path = std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join("claude"))
+            .find(|candidate| candidate.is_file())
+    });
+    if let Some(resolved) = on_path.and_then(|path| std::fs::canonicalize(path).ok()) {
+        if is_cli_js(&resolved) || native_version(&resolved).is_some() {
+            return Some(resolved);
+        }
+    }
+
+    let package = Path::new("node_modules")
+        .join("@anthropic-ai")
+        .join("claude-code")
+        .join("cli.js");
+    let mut candidates = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        candidates.push(home.join(".claude").join("local").join(&package));
+        candidates.push(home.join(".npm-global").join("lib").join(&package));
+    }
+    candidates.push(Path::new("/usr/local/lib").join(&package));
+    candidates.push(Path::new("/usr/lib").join(&package));
+    candidates.push(Path::new("/opt/homebrew/lib").join(&package));
+
+    candidates.into_iter().find(|path| path.is_file())
+}
+
+fn is_cli_js(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == "cli.js")
+}
+
+fn read_version(path: &Path) -> Option<String> {
+    if is_cli_js(path) {
+        let (major, minor, patch) = ClaudeCodePatcher::new(path).ok()?.get_version()?;
+        return Some(format!("{}.{}.{}", major, minor, patch));
+    }
+    native_version(path)
+}
+
+/// Native installs keep each release at `.../versions/<version>`
+fn native_version(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let in_versions_dir = path
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .is_some_and(|dir| dir == "versions");
+    let looks_like_version =
+        name.split('.').count() >= 3 && name.starts_with(|c: char| c.is_ascii_digit());
+    (in_versions_dir && looks_like_version).then(|| name.to_string())
+}
+
+fn modified_secs(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
@@ -0,0 +1,124 @@
+//! Claude Code's own configuration directory and settings files
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+fn config_dir_override() -> Option<PathBuf> {
+    std::env::var("CLAUDE_CONFIG_DIR")
+        .ok()
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// `CLAUDE_CONFIG_DIR` when set, otherwise `~/.claude`
+pub fn config_dir() -> Option<PathBuf> {
+    config_dir_override().or_else(|| Some(dirs::home_dir()?.join(".claude")))
+}
+
+/// Global config with the signed-in account: `.claude.json` in
+/// `CLAUDE_CONFIG_DIR` when set, otherwise in the home directory
+pub fn global_config_file() -> Option<PathBuf> {
+    Some(
+        config_dir_override()
+            .or_else(dirs::home_dir)?
+            .join(".claude.json"),
+    )
+}
+
+/// Root of the project `dir` is in: the nearest directory up from it that
+/// holds `.git`, or `dir` itself outside a repository
+pub fn project_root(dir: &Path) -> PathBuf {
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+/// The parts of Claude Code's settings that ccline follows
+#[derive(Debug, Default)]
+pub struct Settings {
+    /// Process environment over the settings `env` blocks
+    pub env: HashMap<String, String>,
+    /// Whether any settings file sets an `apiKeyHelper`
+    pub api_key_helper: bool,
+}
+
+impl Settings {
+    /// Merge the user `settings.json` with the project's
+    /// `.claude/settings.json` and `.claude/settings.local.json`, later files
+    /// taking precedence
+    pub fn load(project_dir: Option<&Path>) -> Self {
+        let mut settings = Self::default();
+        let mut paths = Vec::new();
+        if let Some(dir) = config_dir() {
+            paths.push(dir.join("settings.json"));
+        }
+        if let Some(project_dir) = project_dir {
+            let project = project_dir.join(".claude");
+            paths.push(project.join("settings.json"));
+            paths.push(project.join("settings.local.json"));
+        }
+
+        for path in paths {
+            let Some(value) = read_json(&path) else {
+                continue;
+            };
+            if let Some(env) = value.get("env").and_then(|env| env.as_object()) {
+                for (key, value) in env {
+                    if let Some(value) = value.as_str() {
+                        settings.env.insert(key.clone(), value.to_string());
+                    }
+                }
+            }
+            if value
+                .get("apiKeyHelper")
+                .and_then(|helper| helper.as_str())
+                .is_some_and(|helper| !helper.trim().is_empty())
+            {
+                settings.api_key_helper = true;
+            }
+        }
+        settings.env.extend(std::env::vars());
+        settings
+    }
+
+    /// A non-empty variable from the process environment or a settings `env` block
+    pub fn var(&self, name: &str) -> Option<String> {
+        self.env
+            .get(name)
+            .filter(|value| !value.trim().is_empty())
+            .cloned()
+    }
+
+    /// A variable that is set to anything but `0` or `false`
+    pub fn flag(&self, name: &str) -> bool {
+        self.var(name)
+            .is_some_and(|value| !matches!(value.trim(), "0" | "false"))
+    }
+}
+
+pub fn read_json(path: &Path) -> Option<Value> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn project_root_is_the_nearest_repository() {
+        let base = std::env::temp_dir().join(format!("ccline-project-root-{}", std::process::id()));
+        let nested = base.join("repo").join("src").join("core");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(base.join("repo").join(".git")).unwrap();
+
+        assert_eq!(project_root(&nested), base.join("repo"));
+        assert_eq!(project_root(&base.join("repo")), base.join("repo"));
+        assert_eq!(project_root(&base), base);
+
+        let _ = fs::remove_dir_all(base);
+    }
+}
@@ -1,5 +1,16 @@
+use crate::config::{CredentialProvider, CredentialsConfig};
+use crate::utils::expand_home;
+use crate::utils::process::{run_with_timeout, TimedOutput};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Environment variable holding a long-lived token (`claude setup-token`)
+const TOKEN_ENV_VAR: &str = "CLAUDE_CODE_OAUTH_TOKEN";
+
+/// How long `credential_command` may take, e.g. waiting on a password manager
+const CREDENTIAL_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Deserialize, Serialize)]
 struct OAuthCredentials {
@@ -20,17 +31,107 @@ struct CredentialsFile {
     claude_ai_oauth: Option<OAuthCredentials>,
 }
 
-pub fn get_oauth_token() -> Option<String> {
-    if cfg!(target_os = "macos") {
-        get_oauth_token_macos()
-    } else {
-        get_oauth_token_file()
+/// An OAuth access token and what is known about it
+///
+/// Expired tokens are reported rather than refreshed: the refresh token is
+/// single-use, and rotating it here would sign Claude Code itself out.
+#[derive(Debug, Clone)]
+pub struct OAuthToken {
+    pub access_token: String,
+    /// Milliseconds since the epoch, when the source records it
+    pub expires_at: Option<u64>,
+    pub subscription_type: Option<String>,
+    pub scopes: Vec<String>,
+    pub provider: CredentialProvider,
+}
+
+impl OAuthToken {
+    pub fn is_expired(&self) -> bool {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= now_ms)
     }
+
+    fn from_credentials(oauth: OAuthCredentials, provider: CredentialProvider) -> Self {
+        Self {
+            access_token: oauth.access_token,
+            expires_at: oauth.expires_at,
+            subscription_type: oauth.subscription_type,
+            scopes: oauth.scopes.unwrap_or_default(),
+            provider,
+        }
+    }
+}
+
+/// Whether `credential_command` is configured and in the provider chain
+pub fn has_command(config: &CredentialsConfig) -> bool {
+    config.credential_command.is_some() && config.providers.contains(&CredentialProvider::Command)
 }
 
-fn get_oauth_token_macos() -> Option<String> {
-    use std::process::Command;
+/// First unexpired token from the configured providers, or the first expired
+/// one when none is valid
+///
+/// `run_command` controls whether `credential_command` may be run; it is
+/// skipped on the render path so a slow secret store never delays the statusline.
+pub fn load_token(config: &CredentialsConfig, run_command: bool) -> Option<OAuthToken> {
+    let mut expired = None;
+    for provider in &config.providers {
+        if *provider == CredentialProvider::Command && !run_command {
+            continue;
+        }
+        let Some(token) = read_provider(*provider, config) else {
+            continue;
+        };
+        if !token.is_expired() {
+            return Some(token);
+        }
+        expired.get_or_insert(token);
+    }
+    expired
+}
 
+fn read_provider(provider: CredentialProvider, config: &CredentialsConfig) -> Option<OAuthToken> {
+    match provider {
+        CredentialProvider::Env => {
+            let token = std::env::var(TOKEN_ENV_VAR).ok()?;
+            let token = token.trim();
+            (!token.is_empty()).then(|| OAuthToken {
+                access_token: token.to_string(),
+                expires_at: None,
+                subscription_type: None,
+                scopes: Vec::new(),
+                provider,
+            })
+        }
+        CredentialProvider::File => {
+            let path = expand_home(config.file.as_deref()?);
+            read_token_from_path(&path, provider)
+        }
+        CredentialProvider::ConfigDir => {
+            let config_dir = std::env::var("CLAUDE_CONFIG_DIR").ok()?;
+            read_token_from_path(
+                &PathBuf::from(config_dir).join(".credentials.json"),
+                provider,
+            )
+        }
+        CredentialProvider::Default => {
+            if cfg!(target_os = "macos") {
+                if let Some(token) = read_token_from_keychain() {
+                    return Some(token);
+                }
+            }
+            read_token_from_path(&get_credentials_path()?, provider)
+        }
+        CredentialProvider::Command => {
+            run_credential_command(config.credential_command.as_deref()?)
+        }
+    }
+}
+
+fn read_token_from_keychain() -> Option<OAuthToken> {
     let user = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
 
     let output = Command::new("security")
@@ -42,42 +143,49 @@ fn get_oauth_token_macos() -> Option<String> {
             "-s",
             "Claude Code-credentials",
         ])
-        .output();
-
-    match output {
-        Ok(output) if output.status.success() => {
-            let json_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if !json_str.is_empty() {
-                if let Ok(creds_file) = serde_json::from_str::<CredentialsFile>(&json_str) {
-                    return creds_file.claude_ai_oauth.map(|oauth| oauth.access_token);
-                }
-            }
-            None
-        }
-        _ => {
-            // Fallback to file-based credentials
-            get_oauth_token_file()
-        }
-    }
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    let json_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let creds_file: CredentialsFile = serde_json::from_str(&json_str).ok()?;
+    creds_file
+        .claude_ai_oauth
+        .map(|oauth| OAuthToken::from_credentials(oauth, CredentialProvider::Default))
 }
 
-fn get_oauth_token_file() -> Option<String> {
-    // Try CLAUDE_CONFIG_DIR first if set (respects explicit user configuration)
-    if let Ok(config_dir) = std::env::var("CLAUDE_CONFIG_DIR") {
-        let config_path = PathBuf::from(config_dir).join(".credentials.json");
-        if let Some(token) = read_token_from_path(&config_path) {
-            return Some(token);
-        }
-    }
+/// Run `credential_command` through the shell; its stdout is either a
+/// credentials JSON document or the bare token
+fn run_credential_command(command: &str) -> Option<OAuthToken> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
 
-    // Fall back to default ~/.claude/.credentials.json
-    if let Some(default_path) = get_credentials_path() {
-        if let Some(token) = read_token_from_path(&default_path) {
-            return Some(token);
-        }
-    }
+    let output = match run_with_timeout(&mut shell, CREDENTIAL_COMMAND_TIMEOUT).ok()? {
+        TimedOutput::Completed(output) if output.status.success() => output,
+        _ => return None,
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stdout = stdout.trim();
 
-    None
+    if let Ok(creds_file) = serde_json::from_str::<CredentialsFile>(stdout) {
+        return creds_file
+            .claude_ai_oauth
+            .map(|oauth| OAuthToken::from_credentials(oauth, CredentialProvider::Command));
+    }
+    (!stdout.is_empty() && !stdout.contains(char::is_whitespace)).then(|| OAuthToken {
+        access_token: stdout.to_string(),
+        expires_at: None,
+        subscription_type: None,
+        scopes: Vec::new(),
+        provider: CredentialProvider::Command,
+    })
 }
 
 fn get_credentials_path() -> Option<PathBuf> {
@@ -85,8 +193,8 @@ fn get_credentials_path() -> Option<PathBuf> {
     Some(home.join(".claude").join(".credentials.json"))
 }
 
-/// Read OAuth token from a credentials file path
-fn read_token_from_path(path: &PathBuf) -> Option<String> {
+/// Read OAuth credentials from a credentials file path
+fn read_token_from_path(path: &Path, provider: CredentialProvider) -> Option<OAuthToken> {
     if !path.exists() {
         return None;
     }
@@ -94,5 +202,7 @@ fn read_token_from_path(path: &PathBuf) -> Option<String> {
     let content = std::fs::read_to_string(path).ok()?;
     let creds_file: CredentialsFile = serde_json::from_str(&content).ok()?;
 
-    creds_file.claude_ai_oauth.map(|oauth| oauth.access_token)
+    creds_file
+        .claude_ai_oauth
+        .map(|oauth| OAuthToken::from_credentials(oauth, provider))
 }
@@ -0,0 +1,194 @@
+//! HTTP agents built from the `[network]` config
+//!
+//! Every request goes through [`NetworkPolicy::agent`], so the offline switch,
+//! proxy, CA bundle and timeout apply the same way to the usage segment and the
+//! update check. Without a configured proxy the standard `HTTPS_PROXY`,
+//! `HTTP_PROXY`, `ALL_PROXY` and `NO_PROXY` variables are used, from the process
+//! environment or from the `env` blocks of Claude Code's settings files.
+
+use crate::config::{Config, NetworkConfig};
+use crate::utils::claude_config::{self, Settings};
+use crate::utils::expand_home;
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::CertificateDer;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum NetworkError {
+    /// `network.offline` is set
+    Offline,
+    /// `network.ca_bundle` could not be loaded
+    CaBundle(String),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Offline => write!(f, "network access is disabled (network.offline)"),
+            Self::CaBundle(reason) => write!(f, "invalid CA bundle: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+pub struct NetworkPolicy {
+    config: NetworkConfig,
+    /// Claude Code settings of the project ccline runs in
+    settings: Settings,
+}
+
+impl NetworkPolicy {
+    /// Policy from the `[network]` section of the config file
+    pub fn load() -> Self {
+        let config = Config::load()
+            .ok()
+            .and_then(|config| config.network)
+            .unwrap_or_default();
+        Self::new(config)
+    }
+
+    pub fn new(config: NetworkConfig) -> Self {
+        Self {
+            config,
+            settings: Settings::load(
+                std::env::current_dir()
+                    .ok()
+                    .map(|dir| claude_config::project_root(&dir))
+                    .as_deref(),
+            ),
+        }
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.config.offline
+    }
+
+    /// `network.timeout`, or `default` when it is not set
+    pub fn timeout_or(&self, default: Duration) -> Duration {
+        self.config
+            .timeout
+            .map(Duration::from_secs)
+            .unwrap_or(default)
+    }
+
+    /// Proxy to reach `url` through, if any
+    pub fn proxy_for(&self, url: &str) -> Option<String> {
+        let host = host_of(url)?;
+        let no_proxy = if self.config.no_proxy.is_empty() {
+            self.var(&["NO_PROXY", "no_proxy"])
+                .map(|list| list.split(',').map(|entry| entry.to_string()).collect())
+                .unwrap_or_default()
+        } else {
+            self.config.no_proxy.clone()
+        };
+        if no_proxy.iter().any(|entry| bypasses(&host, entry)) {
+            return None;
+        }
+
+        if let Some(proxy) = &self.config.proxy {
+            return Some(proxy.clone()).filter(|proxy| !proxy.trim().is_empty());
+        }
+        // HTTP_PROXY is also accepted for https URLs, as Claude Code does
+        let names: &[&str] = if url.starts_with("https://") {
+            &[
+                "HTTPS_PROXY",
+                "https_proxy",
+                "HTTP_PROXY",
+                "http_proxy",
+                "ALL_PROXY",
+                "all_proxy",
+            ]
+        } else {
+            &["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"]
+        };
+        self.var(names)
+    }
+
+    /// Agent for a request to `url`, or why no request may be made
+    pub fn agent(&self, url: &str, timeout: Duration) -> Result<ureq::Agent, NetworkError> {
+        if self.is_offline() {
+            return Err(NetworkError::Offline);
+        }
+
+        let mut builder = ureq::AgentBuilder::new().timeout(timeout);
+        if let Some(proxy) = self.proxy_for(url) {
+            if let Ok(proxy) = ureq::Proxy::new(&proxy) {
+                builder = builder.proxy(proxy);
+            }
+        }
+        if let Some(ca_bundle) = &self.config.ca_bundle {
+            builder = builder.tls_config(tls_config(&expand_home(ca_bundle))?);
+        }
+        Ok(builder.build())
+    }
+
+    /// First non-empty variable of `names`
+    fn var(&self, names: &[&str]) -> Option<String> {
+        names.iter().find_map(|name| self.settings.var(name))
+    }
+}
+
+/// Root store of the bundled web PKI roots plus the certificates in `path`
+fn tls_config(path: &Path) -> Result<Arc<rustls::ClientConfig>, NetworkError> {
+    let error = |reason: String| NetworkError::CaBundle(format!("{}: {}", path.display(), reason));
+
+    let certs = CertificateDer::pem_file_iter(path)
+        .map_err(|e| error(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| error(e.to_string()))?;
+    let mut roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let (added, _) = roots.add_parsable_certificates(certs);
+    if added == 0 {
+        return Err(error("no usable certificates".to_string()));
+    }
+
+    let config = rustls::ClientConfig::builder_with_provider(
+        rustls::crypto::ring::default_provider().into(),
+    )
+    .with_protocol_versions(&[&rustls::version::TLS12, &rustls::version::TLS13])
+    .map_err(|e| error(e.to_string()))?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+/// Lowercased host of a URL, without userinfo or port
+fn host_of(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = if let Some(bracketed) = host_port.strip_prefix('[') {
+        bracketed.split(']').next()?
+    } else {
+        host_port.split(':').next()?
+    };
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+/// Whether a `no_proxy` entry covers `host`: `*`, the host itself, or a
+/// parent domain written as `example.com` or `.example.com`
+fn bypasses(host: &str, entry: &str) -> bool {
+    let entry = entry.trim().to_ascii_lowercase();
+    if entry == "*" {
+        return true;
+    }
+    let entry = entry.trim_start_matches("*.").trim_start_matches('.');
+    let entry = match entry.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None if entry.matches(':').count() == 1 => entry.split(':').next().unwrap_or_default(),
+        None => entry,
+    };
+    !entry.is_empty()
+        && (host == entry
+            || host
+                .strip_suffix(entry)
+                .is_some_and(|prefix| prefix.ends_with('.')))
+}
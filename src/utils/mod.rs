@@ -1,4 +1,20 @@
 pub mod claude_code_patcher;
+pub mod claude_code_version;
+pub mod claude_config;
 pub mod credentials;
+pub mod http;
+pub mod process;
+pub mod reverse_reader;
+pub mod state_file;
 
 pub use claude_code_patcher::{ClaudeCodePatcher, LocationResult};
+
+use std::path::PathBuf;
+
+/// Expand a leading `~/` in a path from the config file
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
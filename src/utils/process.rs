@@ -0,0 +1,143 @@
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Outcome of running a child process under a deadline
+#[derive(Debug)]
+pub enum TimedOutput {
+    /// Process exited before the deadline
+    Completed(Output),
+    /// Deadline passed; the process was killed
+    TimedOut,
+}
+
+/// Run a command, killing it if it does not finish within `timeout`
+///
+/// stdout and stderr are drained on background threads so a chatty child
+/// cannot block on a full pipe while we poll for exit.
+pub fn run_with_timeout(command: &mut Command, timeout: Duration) -> std::io::Result<TimedOutput> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout_reader = child.stdout.take().map(|mut out| {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = out.read_to_end(&mut buf);
+            buf
+        })
+    });
+    let stderr_reader = child.stderr.take().map(|mut err| {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = err.read_to_end(&mut buf);
+            buf
+        })
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(TimedOutput::TimedOut);
+        }
+        thread::sleep(Duration::from_millis(5));
+    };
+
+    let stdout = stdout_reader
+        .and_then(|h| h.join().ok())
+        .unwrap_or_default();
+    let stderr = stderr_reader
+        .and_then(|h| h.join().ok())
+        .unwrap_or_default();
+
+    Ok(TimedOutput::Completed(Output {
+        status,
+        stdout,
+        stderr,
+    }))
+}
+
+/// Start a command that outlives the current process
+///
+/// The child gets its own process group (on Windows, no console) so it is not
+/// killed along with the statusline when Claude Code reaps the render.
+pub fn spawn_detached(command: &mut Command) -> std::io::Result<()> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+
+    command.spawn().map(|_| ())
+}
+
+/// Take a refresh lock so only one session refreshes at a time; a lock older
+/// than `stale_after` seconds is left over from a crashed refresh and is broken
+pub fn acquire_lock(lock_path: &Path, stale_after: u64) -> bool {
+    if let Some(parent) = lock_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+
+    for _ in 0..2 {
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(lock_path)
+        {
+            Ok(_) => return true,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                let abandoned = std::fs::metadata(lock_path)
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .and_then(|modified| modified.elapsed().ok())
+                    .map(|age| age.as_secs() >= stale_after)
+                    .unwrap_or(false);
+                if !abandoned {
+                    return false;
+                }
+                let _ = std::fs::remove_file(lock_path);
+            }
+            Err(_) => return false,
+        }
+    }
+    false
+}
+
+/// Run `ccline <subcommand>` detached under the refresh lock at `lock_path`
+///
+/// The spawned process owns the lock and removes it when done; if the spawn
+/// fails the lock is released straight away.
+pub fn spawn_refresh(lock_path: &Path, stale_after: u64, subcommand: &str) {
+    if !acquire_lock(lock_path, stale_after) {
+        return;
+    }
+
+    let spawned =
+        std::env::current_exe().and_then(|exe| spawn_detached(Command::new(exe).arg(subcommand)));
+    if spawned.is_err() {
+        let _ = std::fs::remove_file(lock_path);
+    }
+}
@@ -0,0 +1,165 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const CHUNK_SIZE: u64 = 64 * 1024;
+const MAX_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Reads a file line by line starting from the end
+///
+/// Only the chunks needed to reach the requested lines are read, so finding
+/// the last few entries of a multi-megabyte JSONL file costs a few reads.
+/// Each item is the byte offset where the line starts and its text without the
+/// `\n` or `\r\n` ending; a file ending in a newline yields an empty line first.
+pub struct ReverseLineReader {
+    file: File,
+    /// Start of the bytes currently held in `buf`
+    pos: u64,
+    buf: Vec<u8>,
+    /// `buf[unscanned..]` is known to contain no newline
+    unscanned: usize,
+    /// Grows while a single line spans several chunks (large tool results)
+    chunk_size: u64,
+    /// Chunk size each line starts from
+    base_chunk_size: u64,
+}
+
+impl ReverseLineReader {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::open_with_chunk_size(path, CHUNK_SIZE)
+    }
+
+    fn open_with_chunk_size<P: AsRef<Path>>(path: P, chunk_size: u64) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let len = file.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            file,
+            pos: len,
+            buf: Vec::new(),
+            unscanned: 0,
+            chunk_size,
+            base_chunk_size: chunk_size,
+        })
+    }
+
+    fn read_previous_chunk(&mut self) -> io::Result<()> {
+        let start = self.pos.saturating_sub(self.chunk_size);
+        let mut chunk = vec![0u8; (self.pos - start) as usize];
+        self.file.seek(SeekFrom::Start(start))?;
+        self.file.read_exact(&mut chunk)?;
+        self.unscanned = chunk.len();
+        chunk.extend_from_slice(&self.buf);
+        self.buf = chunk;
+        self.pos = start;
+        self.chunk_size = (self.chunk_size * 2).min(MAX_CHUNK_SIZE);
+        Ok(())
+    }
+}
+
+fn line_text(line: &[u8]) -> String {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line).into_owned()
+}
+
+impl Iterator for ReverseLineReader {
+    type Item = io::Result<(u64, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(idx) = self.buf[..self.unscanned].iter().rposition(|&b| b == b'\n') {
+                let line = self.buf.split_off(idx + 1);
+                self.buf.truncate(idx);
+                self.unscanned = idx;
+                self.chunk_size = self.base_chunk_size;
+                let offset = self.pos + idx as u64 + 1;
+                return Some(Ok((offset, line_text(&line))));
+            }
+
+            if self.pos == 0 {
+                if self.buf.is_empty() {
+                    return None;
+                }
+                let line = std::mem::take(&mut self.buf);
+                self.unscanned = 0;
+                return Some(Ok((0, line_text(&line))));
+            }
+
+            if let Err(e) = self.read_previous_chunk() {
+                // Stop iterating after reporting the error once
+                self.pos = 0;
+                self.buf.clear();
+                self.unscanned = 0;
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(name: &str, content: &str, chunk_size: u64) -> Vec<(u64, String)> {
+        let path = std::env::temp_dir().join(format!(
+            "ccline-reverse-{}-{}.txt",
+            name,
+            std::process::id()
+        ));
+        std::fs::write(&path, content).unwrap();
+        let lines = ReverseLineReader::open_with_chunk_size(&path, chunk_size)
+            .unwrap()
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        let _ = std::fs::remove_file(&path);
+        lines
+    }
+
+    fn owned(lines: &[(u64, &str)]) -> Vec<(u64, String)> {
+        lines
+            .iter()
+            .map(|&(offset, text)| (offset, text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn lines_come_last_first_with_their_offsets() {
+        let expected = owned(&[(12, ""), (6, "three"), (4, "2"), (0, "one")]);
+        assert_eq!(lines("basic", "one\n2\nthree\n", CHUNK_SIZE), expected);
+    }
+
+    #[test]
+    fn lines_spanning_chunk_boundaries_are_joined() {
+        let content = "first line\nsecond, much longer line\nx\n";
+        let expected = owned(&[
+            (38, ""),
+            (36, "x"),
+            (11, "second, much longer line"),
+            (0, "first line"),
+        ]);
+        for chunk_size in [1, 2, 3, 5, 8, 64] {
+            assert_eq!(
+                lines("chunks", content, chunk_size),
+                expected,
+                "chunk {}",
+                chunk_size
+            );
+        }
+    }
+
+    #[test]
+    fn crlf_endings_are_stripped() {
+        let expected = owned(&[(10, ""), (5, "two"), (0, "one")]);
+        assert_eq!(lines("crlf", "one\r\ntwo\r\n", 4), expected);
+    }
+
+    #[test]
+    fn unterminated_last_line_comes_first() {
+        let expected = owned(&[(4, "partial"), (0, "one")]);
+        assert_eq!(lines("partial", "one\npartial", 3), expected);
+    }
+
+    #[test]
+    fn empty_file_has_no_lines() {
+        assert!(lines("empty", "", 4).is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+//! JSON cache and state files shared by concurrently running sessions
+//!
+//! Writes go to a temporary file in the same directory that is then renamed
+//! over the target, so readers never see a partial file and need no lock.
+//! Writers serialize on an advisory lock held on a `<file>.lock` sidecar, which
+//! lets [`StateFile::update`] load, modify and save without losing another
+//! session's change. A file that fails to parse is moved aside to
+//! `<file>.corrupt` and treated as missing.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+pub struct StateFile {
+    path: PathBuf,
+}
+
+impl StateFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `name` in `~/.claude/ccline`
+    pub fn in_state_dir(name: &str) -> Option<Self> {
+        Some(Self::new(
+            dirs::home_dir()?.join(".claude").join("ccline").join(name),
+        ))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Read the file; `None` when it is missing or was corrupt
+    pub fn load<T: DeserializeOwned>(&self) -> Option<T> {
+        let content = fs::read(&self.path).ok()?;
+        match serde_json::from_slice(&content) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                self.quarantine::<T>();
+                None
+            }
+        }
+    }
+
+    /// Replace the file under the write lock
+    pub fn save<T: Serialize>(&self, value: &T) -> io::Result<()> {
+        let _lock = self.lock()?;
+        self.write(value)
+    }
+
+    /// Load (or default), let `f` modify and save if it returns true, all under
+    /// the write lock; returns the resulting value
+    pub fn update<T, F>(&self, f: F) -> io::Result<T>
+    where
+        T: Default + Serialize + DeserializeOwned,
+        F: FnOnce(&mut T) -> bool,
+    {
+        let _lock = self.lock()?;
+        let mut value = self.load().unwrap_or_default();
+        if f(&mut value) {
+            self.write(&value)?;
+        }
+        Ok(value)
+    }
+
+    fn write<T: Serialize>(&self, value: &T) -> io::Result<()> {
+        let json = serde_json::to_vec(value).map_err(io::Error::other)?;
+        write_atomic(&self.path, &json)
+    }
+
+    /// Held until dropped; creating the sidecar also creates the state directory
+    fn lock(&self) -> io::Result<File> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(sibling(&self.path, "lock"))?;
+        file.lock()?;
+        Ok(file)
+    }
+
+    /// Move an unparseable file aside, unless a writer replaced it meanwhile
+    fn quarantine<T: DeserializeOwned>(&self) {
+        let Ok(_lock) = self.lock() else {
+            return;
+        };
+        let still_corrupt = fs::read(&self.path)
+            .is_ok_and(|content| serde_json::from_slice::<T>(&content).is_err());
+        if still_corrupt {
+            let _ = fs::rename(&self.path, sibling(&self.path, "corrupt"));
+        }
+    }
+}
+
+/// Write `contents` to a temporary sibling and rename it over `path`
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = sibling(path, &format!("{}.tmp", std::process::id()));
+    let result = File::create(&tmp_path)
+        .and_then(|mut file| file.write_all(contents))
+        .and_then(|_| fs::rename(&tmp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// `path` with `.suffix` appended to its file name
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
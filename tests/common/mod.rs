@@ -0,0 +1,505 @@
+//! Shared harness for integration tests: a scripted local HTTP server and a
+//! sandboxed home directory to run the `ccometixline` binary against.
+
+#![allow(dead_code)]
+
+use ccometixline::config::{Config, NetworkConfig, SegmentConfig, SegmentId, StyleMode};
+use ccometixline::ui::themes::ThemePresets;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Claude Code version sent in the statusline input
+pub const CLAUDE_CODE_VERSION: &str = "2.0.14";
+
+/// One scripted reply of the mock server
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub body: String,
+    pub headers: Vec<(String, String)>,
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self::raw(status, &body.to_string())
+    }
+
+    pub fn raw(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            body: body.to_string(),
+            headers: Vec::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    pub fn error(status: u16) -> Self {
+        Self::raw(
+            status,
+            r#"{"error":{"type":"error","message":"scripted failure"}}"#,
+        )
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A request the mock server received
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    /// Header names are lowercased
+    pub headers: HashMap<String, String>,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+/// Local HTTP server answering each request with the next scripted response;
+/// the last one repeats once the script runs out
+pub struct MockServer {
+    addr: String,
+    script: Arc<Mutex<Vec<MockResponse>>>,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    served: Arc<AtomicUsize>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl MockServer {
+    pub fn start(script: Vec<MockResponse>) -> Self {
+        assert!(
+            !script.is_empty(),
+            "mock server needs at least one response"
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = Self {
+            addr,
+            script: Arc::new(Mutex::new(script)),
+            requests: Arc::new(Mutex::new(Vec::new())),
+            served: Arc::new(AtomicUsize::new(0)),
+            stopped: Arc::new(AtomicBool::new(false)),
+        };
+
+        let script = Arc::clone(&server.script);
+        let requests = Arc::clone(&server.requests);
+        let served = Arc::clone(&server.served);
+        let stopped = Arc::clone(&server.stopped);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let script = Arc::clone(&script);
+                let requests = Arc::clone(&requests);
+                let served = Arc::clone(&served);
+                // Slow responses must not hold up later requests
+                thread::spawn(move || handle(stream, &script, &requests, &served));
+            }
+        });
+        server
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Replace the responses given to later requests
+    pub fn script(&self, script: Vec<MockResponse>) {
+        assert!(
+            !script.is_empty(),
+            "mock server needs at least one response"
+        );
+        *self.script.lock().unwrap() = script;
+        self.served.store(0, Ordering::SeqCst);
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag
+        let _ = TcpStream::connect(&self.addr);
+    }
+}
+
+fn handle(
+    mut stream: TcpStream,
+    script: &Mutex<Vec<MockResponse>>,
+    requests: &Mutex<Vec<RecordedRequest>>,
+    served: &AtomicUsize,
+) {
+    let Some(request) = read_request(&stream) else {
+        return;
+    };
+    requests.lock().unwrap().push(request);
+
+    let response = {
+        let script = script.lock().unwrap();
+        let index = served.fetch_add(1, Ordering::SeqCst).min(script.len() - 1);
+        script[index].clone()
+    };
+    thread::sleep(response.delay);
+
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason(response.status),
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    // The client may have given up on a slow response already
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(response.body.as_bytes());
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+fn read_request(stream: &TcpStream) -> Option<RecordedRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+    })
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Status",
+    }
+}
+
+/// A temporary home directory holding the ccline config, OAuth credentials
+/// and state files, removed on drop
+pub struct Sandbox {
+    root: PathBuf,
+    env: Vec<(String, String)>,
+}
+
+impl Sandbox {
+    pub fn new() -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "ccline-test-{}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("home").join(".claude").join("ccline")).unwrap();
+        std::fs::create_dir_all(root.join("bin")).unwrap();
+        std::fs::create_dir_all(root.join("project")).unwrap();
+        Self {
+            root,
+            env: Vec::new(),
+        }
+    }
+
+    /// Set an environment variable for every command run in the sandbox
+    pub fn set_env(&mut self, name: &str, value: &str) {
+        self.env.push((name.to_string(), value.to_string()));
+    }
+
+    pub fn home(&self) -> PathBuf {
+        self.root.join("home")
+    }
+
+    pub fn state_dir(&self) -> PathBuf {
+        self.home().join(".claude").join("ccline")
+    }
+
+    /// Write a config with only the usage segment enabled, in plain mode
+    pub fn write_usage_config(&self, options: &[(&str, toml::Value)]) {
+        let mut config: Config = ThemePresets::get_theme("default");
+        config.style.mode = StyleMode::Plain;
+        for segment in &mut config.segments {
+            segment.enabled = segment.id == SegmentId::Usage;
+        }
+        let usage: &mut SegmentConfig = config
+            .segments
+            .iter_mut()
+            .find(|segment| segment.id == SegmentId::Usage)
+            .expect("usage segment in default theme");
+        usage.options.clear();
+        for (key, value) in options {
+            let value = serde_json::to_value(value).unwrap();
+            usage.options.insert(key.to_string(), value);
+        }
+        self.write_config(&config);
+    }
+
+    /// Set the `[network]` section of the written config
+    pub fn write_network(&self, network: NetworkConfig) {
+        let path = self.state_dir().join("config.toml");
+        let content = std::fs::read_to_string(&path).expect("config written first");
+        let mut config: Config = toml::from_str(&content).expect("parse config");
+        config.network = Some(network);
+        self.write_config(&config);
+    }
+
+    pub fn write_config(&self, config: &Config) {
+        let content = toml::to_string(config).expect("serialize config");
+        std::fs::write(self.state_dir().join("config.toml"), content).unwrap();
+    }
+
+    /// Store an OAuth token in `~/.claude/.credentials.json`
+    pub fn write_token(&self, token: &str, expires_at_ms: u64) {
+        let credentials = serde_json::json!({
+            "claudeAiOauth": {
+                "accessToken": token,
+                "refreshToken": "refresh",
+                "expiresAt": expires_at_ms,
+                "scopes": ["user:inference", "user:profile"],
+                "subscriptionType": "max",
+            }
+        });
+        std::fs::write(
+            self.home().join(".claude").join(".credentials.json"),
+            credentials.to_string(),
+        )
+        .unwrap();
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_ccometixline"));
+        command
+            .env("HOME", self.home())
+            .env("USERPROFILE", self.home())
+            // Keep the host's Claude Code install and credentials out of the test
+            .env("PATH", self.root.join("bin"))
+            .env_remove("CLAUDE_CONFIG_DIR")
+            .env_remove("CLAUDE_CODE_OAUTH_TOKEN")
+            .current_dir(self.root.join("project"));
+        for name in [
+            "HTTPS_PROXY",
+            "https_proxy",
+            "HTTP_PROXY",
+            "http_proxy",
+            "ALL_PROXY",
+            "all_proxy",
+            "NO_PROXY",
+            "no_proxy",
+        ] {
+            command.env_remove(name);
+        }
+        command.envs(self.env.iter().map(|(name, value)| (name, value)));
+        command
+    }
+
+    /// Render the statusline and return it without ANSI escapes
+    pub fn render(&self) -> String {
+        let input = serde_json::json!({
+            "model": { "id": "claude-sonnet-4-5", "display_name": "Sonnet 4.5" },
+            "workspace": { "current_dir": self.root.join("project") },
+            "transcript_path": self.root.join("transcript.jsonl"),
+            "version": CLAUDE_CODE_VERSION,
+        });
+        let mut child = self
+            .command()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn ccometixline");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.to_string().as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success(), "render failed");
+        strip_ansi(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Run the binary with `args` and collect its output
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.command()
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .expect("run ccometixline")
+    }
+
+    /// Run the refresh a render would start in the background, and wait for it
+    pub fn refresh(&self) {
+        let status = self
+            .command()
+            .arg("refresh-usage")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .expect("run refresh-usage");
+        assert!(status.success(), "refresh-usage failed");
+    }
+
+    pub fn cache_path(&self) -> PathBuf {
+        self.state_dir().join(".api_usage_cache.json")
+    }
+
+    pub fn lock_path(&self) -> PathBuf {
+        self.state_dir().join(".api_usage_cache.lock")
+    }
+
+    pub fn cache(&self) -> Option<serde_json::Value> {
+        let content = std::fs::read_to_string(self.cache_path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn write_cache(&self, cache: &serde_json::Value) {
+        std::fs::write(self.cache_path(), cache.to_string()).unwrap();
+    }
+
+    /// Make the cache look old and any backoff already elapsed, as if the
+    /// next render came much later
+    pub fn age_cache(&self) {
+        let mut cache = self.cache().expect("usage cache");
+        let past = "2020-01-01T00:00:00+00:00";
+        if cache.get("cached_at").is_some_and(|v| !v.is_null()) {
+            cache["cached_at"] = past.into();
+        }
+        if let Some(error) = cache.get_mut("error").and_then(|e| e.as_object_mut()) {
+            error.insert("retry_at".to_string(), past.into());
+        }
+        self.write_cache(&cache);
+    }
+
+    /// Wait for a background refresh to release its lock
+    pub fn wait_for_refresh(&self, timeout: Duration) -> bool {
+        wait_until(timeout, || !self.lock_path().exists())
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+pub fn wait_until(timeout: Duration, mut done: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if done() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(25));
+    }
+    done()
+}
+
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if chars.peek() == Some(&'[') {
+                chars.next();
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            } else if chars.peek() == Some(&']') {
+                // OSC sequences (hyperlinks) end with ST or BEL
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Usage API response with every bucket the segment shows
+pub fn usage_body() -> serde_json::Value {
+    serde_json::json!({
+        "five_hour": { "utilization": 42.0, "resets_at": "2099-01-01T05:00:00+00:00" },
+        "seven_day": { "utilization": 13.0, "resets_at": "2099-01-07T00:00:00+00:00" },
+        "seven_day_oauth_apps": null,
+        "seven_day_opus": { "utilization": 71.0, "resets_at": null },
+        "extra_usage": { "is_enabled": true, "monthly_limit": 5000, "used_credits": 1234, "utilization": 24.68 },
+    })
+}
+
+/// Sandbox with the usage segment pointed at `api_base_url` and a valid token
+pub fn usage_sandbox(api_base_url: &str) -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.write_usage_config(&[
+        ("api_base_url", api_base_url.into()),
+        ("timeout", 1.into()),
+        ("reset_format", "none".into()),
+    ]);
+    sandbox.write_token("token-a", far_future_ms());
+    sandbox
+}
+
+/// Token expiry far in the future, in milliseconds
+pub fn far_future_ms() -> u64 {
+    4_102_444_800_000 // 2100-01-01
+}
@@ -0,0 +1,172 @@
+//! The `[network]` policy: offline mode and proxy selection, observed through
+//! the usage segment's requests to a local mock server.
+
+#![cfg(unix)]
+
+mod common;
+
+use ccometixline::config::NetworkConfig;
+use common::{far_future_ms, usage_body, usage_sandbox, MockResponse, MockServer, Sandbox};
+use serde_json::json;
+use std::net::TcpListener;
+use std::time::Duration;
+
+/// Upstream that only a proxy can reach
+const UNREACHABLE_BASE_URL: &str = "http://usage.invalid";
+
+/// Address nothing listens on, for a proxy that must not be used
+fn closed_port_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{}", addr)
+}
+
+#[test]
+fn offline_mode_makes_no_requests() {
+    let server = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let sandbox = usage_sandbox(&server.url());
+    sandbox.write_network(NetworkConfig {
+        offline: true,
+        ..Default::default()
+    });
+
+    assert!(sandbox.render().contains("offline"));
+    assert!(
+        !sandbox.lock_path().exists(),
+        "no background refresh started"
+    );
+    sandbox.refresh();
+
+    assert_eq!(server.request_count(), 0);
+    assert!(sandbox.cache().is_none());
+}
+
+#[test]
+fn offline_mode_marks_old_figures() {
+    let server = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let sandbox = usage_sandbox(&server.url());
+    sandbox.refresh();
+    assert!(!sandbox.render().contains("stale"));
+
+    sandbox.write_network(NetworkConfig {
+        offline: true,
+        ..Default::default()
+    });
+    sandbox.age_cache();
+
+    let output = sandbox.render();
+    assert!(output.contains("5h 42%"), "{output}");
+    assert!(output.contains("stale"), "{output}");
+    assert_eq!(server.request_count(), 1);
+}
+
+#[test]
+fn proxy_is_taken_from_the_environment() {
+    let proxy = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let mut sandbox = usage_sandbox(UNREACHABLE_BASE_URL);
+    sandbox.set_env("HTTP_PROXY", &proxy.url());
+
+    sandbox.refresh();
+
+    let requests = proxy.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].path,
+        format!("{}/api/oauth/usage", UNREACHABLE_BASE_URL)
+    );
+    assert!(sandbox.render().contains("5h 42%"));
+}
+
+#[test]
+fn proxy_is_taken_from_claude_settings() {
+    let proxy = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let sandbox = usage_sandbox(UNREACHABLE_BASE_URL);
+    let settings = json!({ "env": { "HTTP_PROXY": proxy.url() } });
+    std::fs::write(
+        sandbox.home().join(".claude").join("settings.json"),
+        settings.to_string(),
+    )
+    .unwrap();
+
+    sandbox.refresh();
+
+    assert_eq!(proxy.request_count(), 1);
+}
+
+#[test]
+fn configured_proxy_overrides_the_environment() {
+    let proxy = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let mut sandbox = usage_sandbox(UNREACHABLE_BASE_URL);
+    sandbox.set_env("HTTP_PROXY", &closed_port_url());
+    sandbox.write_network(NetworkConfig {
+        proxy: Some(proxy.url()),
+        ..Default::default()
+    });
+
+    sandbox.refresh();
+
+    assert_eq!(proxy.request_count(), 1);
+    assert!(sandbox.cache().unwrap().get("error").is_none());
+}
+
+#[test]
+fn no_proxy_hosts_are_reached_directly() {
+    let server = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let mut sandbox = usage_sandbox(&server.url());
+    sandbox.set_env("HTTP_PROXY", &closed_port_url());
+    sandbox.set_env("NO_PROXY", "localhost,127.0.0.1");
+
+    sandbox.refresh();
+
+    assert_eq!(server.requests()[0].path, "/api/oauth/usage");
+    assert!(sandbox.render().contains("5h 42%"));
+}
+
+#[test]
+fn configured_no_proxy_takes_precedence() {
+    let server = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let sandbox = usage_sandbox(&server.url());
+    sandbox.write_network(NetworkConfig {
+        proxy: Some(closed_port_url()),
+        no_proxy: vec!["127.0.0.1".to_string()],
+        ..Default::default()
+    });
+
+    sandbox.refresh();
+
+    assert_eq!(server.request_count(), 1);
+}
+
+#[test]
+fn global_timeout_applies_without_a_segment_timeout() {
+    let server = MockServer::start(vec![
+        MockResponse::json(200, usage_body()).with_delay(Duration::from_millis(1500))
+    ]);
+    // Fast enough for the segment's default 2s timeout, not for the global one
+    let sandbox = Sandbox::new();
+    sandbox.write_usage_config(&[("api_base_url", server.url().into())]);
+    sandbox.write_token("token-a", far_future_ms());
+    sandbox.write_network(NetworkConfig {
+        timeout: Some(1),
+        ..Default::default()
+    });
+
+    sandbox.refresh();
+
+    let cache = sandbox.cache().expect("error recorded");
+    assert_eq!(cache["error"]["kind"], "network");
+}
+
+#[test]
+fn missing_ca_bundle_fails_the_config_check() {
+    let sandbox = usage_sandbox(UNREACHABLE_BASE_URL);
+    sandbox.write_network(NetworkConfig {
+        ca_bundle: Some("/nonexistent/ca.pem".to_string()),
+        ..Default::default()
+    });
+
+    let output = sandbox.run(&["--check"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("CA bundle not found"));
+}
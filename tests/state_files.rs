@@ -0,0 +1,86 @@
+//! Cache and state files shared between sessions: corrupt files are moved
+//! aside, old layouts still load, and concurrent writers leave valid JSON.
+
+#![cfg(unix)]
+
+mod common;
+
+use common::{usage_body, usage_sandbox, MockResponse, MockServer, Sandbox};
+use serde_json::json;
+use std::thread;
+use std::time::Duration;
+
+/// Files in the state directory whose name ends with `suffix`
+fn state_files_ending_with(sandbox: &Sandbox, suffix: &str) -> Vec<String> {
+    std::fs::read_dir(sandbox.state_dir())
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(suffix))
+        .collect()
+}
+
+#[test]
+fn corrupt_cache_is_moved_aside_and_refetched() {
+    let server = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let sandbox = usage_sandbox(&server.url());
+    std::fs::create_dir_all(sandbox.state_dir()).unwrap();
+    std::fs::write(sandbox.cache_path(), "{\"buckets\": {\"five_h").unwrap();
+
+    sandbox.refresh();
+
+    let corrupt = sandbox.state_dir().join(".api_usage_cache.json.corrupt");
+    assert_eq!(
+        std::fs::read_to_string(corrupt).unwrap(),
+        "{\"buckets\": {\"five_h"
+    );
+    assert_eq!(server.request_count(), 1);
+    assert!(sandbox.render().contains("5h 42%"));
+}
+
+#[test]
+fn legacy_cache_is_still_read() {
+    let server = MockServer::start(vec![MockResponse::error(500)]);
+    let sandbox = usage_sandbox(&server.url());
+    std::fs::create_dir_all(sandbox.state_dir()).unwrap();
+    sandbox.write_cache(&json!({
+        "five_hour_utilization": 42.0,
+        "seven_day_utilization": 13.0,
+        "resets_at": null,
+        "cached_at": chrono::Utc::now().to_rfc3339(),
+    }));
+
+    let output = sandbox.render();
+    assert!(output.contains("5h 42%"), "{output}");
+    assert!(output.contains("7d 13%"), "{output}");
+
+    sandbox.refresh();
+    assert_eq!(
+        server.request_count(),
+        0,
+        "fresh legacy cache is used as is"
+    );
+}
+
+#[test]
+fn concurrent_writers_leave_valid_files() {
+    let server = MockServer::start(vec![
+        MockResponse::json(200, usage_body()).with_delay(Duration::from_millis(50))
+    ]);
+    let sandbox = usage_sandbox(&server.url());
+
+    thread::scope(|scope| {
+        for _ in 0..8 {
+            scope.spawn(|| sandbox.refresh());
+            scope.spawn(|| sandbox.render());
+        }
+    });
+    // Renders that ran before the first refresh finished may have spawned one
+    assert!(sandbox.wait_for_refresh(Duration::from_secs(5)));
+
+    let cache = sandbox.cache().expect("cache is valid JSON");
+    assert_eq!(cache["buckets"]["five_hour"]["utilization"], 42.0);
+    assert!(state_files_ending_with(&sandbox, ".corrupt").is_empty());
+    assert!(state_files_ending_with(&sandbox, ".tmp").is_empty());
+    assert!(sandbox.render().contains("5h 42%"));
+}
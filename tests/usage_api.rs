@@ -0,0 +1,286 @@
+//! The usage segment's network path, run against a local mock of the OAuth
+//! usage endpoint.
+
+#![cfg(unix)]
+
+mod common;
+
+use chrono::{DateTime, Utc};
+use common::{
+    far_future_ms, usage_body, usage_sandbox, MockResponse, MockServer, CLAUDE_CODE_VERSION,
+};
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+fn error_field<'a>(cache: &'a serde_json::Value, field: &str) -> &'a serde_json::Value {
+    &cache["error"][field]
+}
+
+/// Seconds from now until the cached error allows another request
+fn backoff_remaining(cache: &serde_json::Value) -> i64 {
+    let retry_at = error_field(cache, "retry_at").as_str().expect("retry_at");
+    let retry_at: DateTime<Utc> = retry_at.parse().expect("retry_at timestamp");
+    (retry_at - Utc::now()).num_seconds()
+}
+
+#[test]
+fn success_caches_every_bucket_and_renders_windows() {
+    let server = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let sandbox = usage_sandbox(&server.url());
+
+    sandbox.refresh();
+
+    let cache = sandbox.cache().expect("cache written");
+    let buckets = cache["buckets"].as_object().unwrap();
+    assert!(buckets.contains_key("five_hour"));
+    assert!(buckets.contains_key("seven_day_opus"));
+    assert!(buckets.contains_key("extra_usage"));
+    assert!(
+        !buckets.contains_key("seven_day_oauth_apps"),
+        "null buckets are dropped"
+    );
+    assert!(cache["cached_at"].is_string());
+    assert!(cache.get("error").is_none());
+
+    let output = sandbox.render();
+    assert!(output.contains("5h 42%"), "{output}");
+    assert!(output.contains("7d 13%"), "{output}");
+    assert!(!output.contains("stale"), "{output}");
+}
+
+#[test]
+fn request_carries_token_and_claude_code_user_agent() {
+    let server = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let sandbox = usage_sandbox(&server.url());
+
+    // A render records the Claude Code version from its input
+    sandbox.render();
+    sandbox.wait_for_refresh(Duration::from_secs(5));
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.method, "GET");
+    assert_eq!(request.path, "/api/oauth/usage");
+    assert_eq!(request.header("authorization"), Some("Bearer token-a"));
+    assert_eq!(request.header("anthropic-beta"), Some("oauth-2025-04-20"));
+    assert_eq!(
+        request.header("user-agent"),
+        Some(format!("claude-code/{}", CLAUDE_CODE_VERSION).as_str())
+    );
+}
+
+#[test]
+fn selected_windows_can_name_any_bucket() {
+    let server = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let sandbox = usage_sandbox(&server.url());
+    sandbox.write_usage_config(&[
+        ("api_base_url", server.url().into()),
+        ("reset_format", "none".into()),
+        (
+            "windows",
+            toml::Value::Array(vec!["seven_day_opus".into(), "extra_usage".into()]),
+        ),
+    ]);
+
+    sandbox.refresh();
+
+    let output = sandbox.render();
+    assert!(output.contains("7d opus 71%"), "{output}");
+    assert!(output.contains("extra 25%"), "{output}");
+}
+
+#[test]
+fn partial_payload_skips_missing_and_null_buckets() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        json!({
+            "five_hour": null,
+            "seven_day": { "utilization": 55.0, "resets_at": null },
+        }),
+    )]);
+    let sandbox = usage_sandbox(&server.url());
+
+    sandbox.refresh();
+
+    let cache = sandbox.cache().unwrap();
+    assert_eq!(
+        cache["buckets"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>(),
+        vec!["seven_day"]
+    );
+    let output = sandbox.render();
+    assert!(output.contains("55%"), "{output}");
+    assert!(!output.contains("5h"), "{output}");
+}
+
+#[test]
+fn malformed_body_is_an_api_error() {
+    let server = MockServer::start(vec![MockResponse::raw(200, "<html>maintenance</html>")]);
+    let sandbox = usage_sandbox(&server.url());
+
+    sandbox.refresh();
+
+    let cache = sandbox.cache().unwrap();
+    assert_eq!(error_field(&cache, "kind"), "api");
+    assert!(sandbox.render().contains("api error"));
+}
+
+#[test]
+fn unauthorized_backs_off_until_the_token_changes() {
+    let server = MockServer::start(vec![MockResponse::error(401)]);
+    let sandbox = usage_sandbox(&server.url());
+
+    sandbox.refresh();
+
+    let cache = sandbox.cache().unwrap();
+    assert_eq!(error_field(&cache, "kind"), "auth");
+    assert_eq!(error_field(&cache, "status"), 401);
+    assert_eq!(error_field(&cache, "failures"), 1);
+    assert!((25..=30).contains(&backoff_remaining(&cache)));
+    assert!(sandbox.render().contains("auth expired"));
+
+    // Within the backoff the same token is not retried
+    sandbox.refresh();
+    assert_eq!(server.request_count(), 1);
+
+    // Signing in again retries right away, and success clears the error
+    server.script(vec![MockResponse::json(200, usage_body())]);
+    sandbox.write_token("token-b", far_future_ms());
+    sandbox.refresh();
+    assert_eq!(server.request_count(), 2);
+    let cache = sandbox.cache().unwrap();
+    assert!(cache.get("error").is_none());
+    assert!(sandbox.render().contains("5h 42%"));
+}
+
+#[test]
+fn auth_failure_marks_cached_usage() {
+    let server = MockServer::start(vec![
+        MockResponse::json(200, usage_body()),
+        MockResponse::error(401),
+    ]);
+    let sandbox = usage_sandbox(&server.url());
+
+    sandbox.refresh();
+    sandbox.age_cache();
+    sandbox.refresh();
+
+    let output = sandbox.render();
+    assert!(output.contains("5h 42%"), "{output}");
+    assert!(output.contains("auth expired"), "{output}");
+}
+
+#[test]
+fn rate_limit_honours_retry_after() {
+    let server = MockServer::start(vec![
+        MockResponse::json(200, usage_body()),
+        MockResponse::error(429).with_header("Retry-After", "120"),
+    ]);
+    let sandbox = usage_sandbox(&server.url());
+
+    sandbox.refresh();
+    sandbox.age_cache();
+    sandbox.refresh();
+
+    let cache = sandbox.cache().unwrap();
+    assert_eq!(error_field(&cache, "kind"), "rate_limited");
+    assert!((115..=120).contains(&backoff_remaining(&cache)));
+    // The last good figures are kept and marked
+    assert_eq!(cache["buckets"]["five_hour"]["utilization"], 42.0);
+    let output = sandbox.render();
+    assert!(output.contains("5h 42%"), "{output}");
+    assert!(output.contains("stale"), "{output}");
+
+    sandbox.refresh();
+    assert_eq!(server.request_count(), 2, "no request during Retry-After");
+}
+
+#[test]
+fn repeated_server_errors_double_the_backoff() {
+    let server = MockServer::start(vec![MockResponse::error(500)]);
+    let sandbox = usage_sandbox(&server.url());
+
+    sandbox.refresh();
+    let cache = sandbox.cache().unwrap();
+    assert_eq!(error_field(&cache, "kind"), "server");
+    assert!((25..=30).contains(&backoff_remaining(&cache)));
+    assert!(sandbox.render().contains("server error"));
+
+    sandbox.age_cache();
+    sandbox.refresh();
+    let cache = sandbox.cache().unwrap();
+    assert_eq!(error_field(&cache, "failures"), 2);
+    assert!((55..=60).contains(&backoff_remaining(&cache)));
+
+    sandbox.age_cache();
+    sandbox.refresh();
+    let cache = sandbox.cache().unwrap();
+    assert_eq!(error_field(&cache, "failures"), 3);
+    assert!((115..=120).contains(&backoff_remaining(&cache)));
+    assert_eq!(server.request_count(), 3);
+}
+
+#[test]
+fn slow_response_times_out_without_blocking_renders() {
+    let server = MockServer::start(vec![
+        MockResponse::json(200, usage_body()).with_delay(Duration::from_secs(3))
+    ]);
+    let sandbox = usage_sandbox(&server.url());
+
+    // The render only starts the refresh; it never waits on the network
+    let started = Instant::now();
+    sandbox.render();
+    assert!(started.elapsed() < Duration::from_secs(1));
+
+    assert!(sandbox.wait_for_refresh(Duration::from_secs(10)));
+    let cache = sandbox.cache().expect("error recorded");
+    assert_eq!(error_field(&cache, "kind"), "network");
+    assert!(sandbox.render().contains("offline"));
+}
+
+#[test]
+fn concurrent_renders_start_a_single_refresh() {
+    let server = MockServer::start(vec![
+        MockResponse::json(200, usage_body()).with_delay(Duration::from_millis(300))
+    ]);
+    let sandbox = usage_sandbox(&server.url());
+
+    for _ in 0..4 {
+        sandbox.render();
+    }
+    assert!(sandbox.wait_for_refresh(Duration::from_secs(10)));
+
+    assert_eq!(server.request_count(), 1);
+    assert!(sandbox.render().contains("5h 42%"));
+}
+
+#[test]
+fn fresh_cache_is_not_refetched() {
+    let server = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let sandbox = usage_sandbox(&server.url());
+
+    sandbox.refresh();
+    sandbox.refresh();
+    sandbox.render();
+    assert!(sandbox.wait_for_refresh(Duration::from_secs(5)));
+
+    assert_eq!(server.request_count(), 1);
+}
+
+#[test]
+fn expired_token_is_reported_without_a_request() {
+    let server = MockServer::start(vec![MockResponse::json(200, usage_body())]);
+    let sandbox = usage_sandbox(&server.url());
+    sandbox.write_token("token-a", 1_000);
+
+    assert!(sandbox.render().contains("token expired"));
+    sandbox.refresh();
+
+    assert_eq!(server.request_count(), 0);
+    let cache = sandbox.cache().unwrap();
+    assert_eq!(error_field(&cache, "kind"), "expired");
+}